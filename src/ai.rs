@@ -1,10 +1,23 @@
-use std::collections::HashMap;
+use std::{ops::RangeInclusive, sync::atomic::{AtomicBool, Ordering}, time::{Duration, Instant}};
 
 use rand::{thread_rng, seq::SliceRandom};
 
 use super::*;
 
-pub struct AI {}
+pub struct AI {
+    /// Hashes of the positions of the game so far, to score repetitions as draws.
+    history: Vec<u64>,
+    /// Shared by the threads of a search and kept from one move to the next.
+    table: TranspositionTable,
+    /// Deepest iteration to search, however much time is left.
+    max_depth: i16,
+    /// Number of threads searching; with one the search is deterministic.
+    threads: usize,
+    /// Perfect results of positions with few pieces, used in the search and to play them at the root.
+    tablebase: Option<Tablebase>,
+    /// Moves played while the game is still in the opening.
+    book: Option<OpeningBook>
+}
 
 fn ori_score(val: i64, my_t: Color, n_t: Color) -> i64 {
     if my_t != n_t {
@@ -13,228 +26,453 @@ fn ori_score(val: i64, my_t: Color, n_t: Color) -> i64 {
     return val;
 }
 
+impl Default for AI {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl AI {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            history: vec![],
+            table: TranspositionTable::with_size_mb(64),
+            max_depth: MAX_DEPTH,
+            threads: 1,
+            tablebase: None,
+            book: None
+        }
+    }
+    pub fn set_book(&mut self, book: OpeningBook) {
+        self.book = Some(book);
+    }
+    pub fn set_tablebase(&mut self, tablebase: Tablebase) {
+        self.tablebase = Some(tablebase);
+    }
+    /// Searches with `threads` threads, one by default. The helpers are spawned for each move and joined
+    /// before it is returned; with more than one thread the move and its score depend on their timing.
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
     }
-    pub fn compute_move(&mut self, board: &Board) -> PieceMove {
+    pub fn set_max_depth(&mut self, depth: i16) {
+        self.max_depth = depth.clamp(1, MAX_DEPTH);
+    }
+    pub fn set_history(&mut self, history: Vec<u64>) {
+        self.history = history;
+    }
+    /// Searches one ply deeper each iteration until `budget` runs out, and plays the best move of the last finished one.
+    /// Returns None when there is no legal move.
+    pub fn compute_move(&mut self, board: &Board, budget: Duration) -> Option<Move> {
+        let started = Instant::now();
+        let deadline = started + budget;
+        self.table.new_search();
 
         let mut board = board.clone();
 
-        let mut to_explore = if board.must_jump.len() != 0 {
-            board.must_jump.clone()
+        if let Some(mv) = self.book.as_ref().and_then(|book| book.pick_move(&board, &mut thread_rng())) {
+            eprintln!("Book move");
+            return Some(mv);
         }
-        else {
-            board.piece_pos(board.turn)
-        };
 
-        let mut rng = thread_rng();
-        to_explore.shuffle(&mut rng);
-
-        let to_explore = sort_by_heuristic(board.clone(), to_explore, heuristic);
-
-        let old_board = board.clone();
-        let mut best_move: Option<(i64, PieceMove)> = None;
-        let mut thr_handles = vec![];
-        for cp in to_explore {
-            for dir in DIRS {
-                let pm = PieceMove {pos: cp, dir};
-                if board.make_move(pm) {
-                    thr_handles.push(std::thread::spawn(move || {
-                        let mp = if board.turn == old_board.turn {1} else {-1};
-                        (evaluate(board)*mp, pm)
-                    }));
-                    board = old_board.clone();
+        let mut to_explore = board.legal_moves();
+
+        // Vary the play between equal moves, unless a single thread should give the same move every time.
+        if self.threads > 1 {
+            let mut rng = thread_rng();
+            to_explore.shuffle(&mut rng);
+        }
+
+        if let Some(tablebase) = &self.tablebase {
+            match tablebase.best_move(&board) {
+                Some((mv, value @ (TablebaseValue::Win(Some(_)) | TablebaseValue::Loss(Some(_))))) => {
+                    eprintln!("Tablebase: {:?}", value);
+                    return Some(mv);
                 }
+                // Search for the best of the moves that keep the draw, or the win when its distance isn't known.
+                Some((_, value)) => to_explore.retain(|mv| tablebase.keeps_value(&board, mv, value)),
+                None => {}
             }
         }
-        for handle in thr_handles {
-            let (score, mv) = handle.join().unwrap();
-            if let Some(best_move) = &mut best_move {
-                if score > best_move.0 {
-                    best_move.0 = score;
-                    best_move.1 = mv;
-                }
+
+        let to_explore = sort_by_heuristic(&mut board, to_explore, heuristic);
+        if to_explore.len() <= 1 {
+            return to_explore.into_iter().next();
+        }
+
+        let roots = to_explore.into_iter().map(|mv| {
+            let undo = board.apply_unchecked(&mv);
+            let child = board.clone();
+            board.unmake_move(undo);
+            // A position seen twice before would be the third repetition.
+            let repeated = self.history.iter().filter(|&&hash| hash == child.hash).count() >= 2;
+            (mv, child, repeated)
+        }).collect::<Vec<_>>();
+
+        // Lazy SMP: helpers search the same root and only help through the table; the main search picks the move.
+        let stop = AtomicBool::new(false);
+        let (best_move, search, helper_nodes) = std::thread::scope(|scope| {
+            let helpers = (1..self.threads).map(|id| {
+                let mut search = Search::new(&self.table, self.tablebase.as_ref(), deadline, &stop);
+                search.roots = roots.clone();
+                let max_depth = self.max_depth;
+                scope.spawn(move || {
+                    // Starting every other helper one ply deeper spreads them over more of the tree.
+                    deepen(&mut search, 1 + (id % 2) as i16..=max_depth, deadline);
+                    search.nodes
+                })
+            }).collect::<Vec<_>>();
+
+            let mut search = Search::new(&self.table, self.tablebase.as_ref(), deadline, &stop);
+            search.roots = roots;
+            // A deeper iteration takes longer than all the previous ones together, so none is started past half the budget.
+            let best_move = deepen(&mut search, 1..=self.max_depth, started + budget/2);
+            stop.store(true, Ordering::Relaxed);
+            let helper_nodes = helpers.into_iter().map(|helper| helper.join().unwrap()).sum::<u64>();
+            (best_move, search, helper_nodes)
+        });
+        match best_move {
+            Some((eval, depth)) => eprintln!("Eval: {:?} (depth {})", eval, depth),
+            None => eprintln!("No iteration finished in time")
+        }
+        eprintln!("Nodes: {}", search.nodes + helper_nodes);
+        let stats = self.table.stats();
+        eprintln!("Table: {} probes, {} hits, {} stores, {} overwritten", stats.probes, stats.hits, stats.stores, stats.overwrites);
+        search.roots.into_iter().next().map(|(mv, ..)| mv)
+    }
+}
+
+/// Searches one ply deeper each iteration over `depths`, starting no new iteration after `soft_deadline`.
+/// Returns the score and depth of the last finished iteration.
+fn deepen(search: &mut Search, depths: RangeInclusive<i16>, soft_deadline: Instant) -> Option<(i64, i16)> {
+    let mut best_move = None;
+    for depth in depths {
+        // Search a window around the last score first, and widen the side it falls out of.
+        let mut window = ASPIRATION_WINDOW;
+        let (mut alpha, mut beta) = match best_move {
+            Some((eval, _)) => (eval - window, eval + window),
+            None => (LOST, WIN)
+        };
+        let eval = loop {
+            let Some(eval) = search_root(depth, alpha, beta, search) else {
+                return best_move;
+            };
+            if eval <= alpha && alpha > LOST {
+                alpha = (eval - window).max(LOST);
+            }
+            else if eval >= beta && beta < WIN {
+                beta = (eval + window).min(WIN);
+            }
+            else {
+                break eval;
+            }
+            window *= 4;
+        };
+        best_move = Some((eval, depth));
+
+        if Instant::now() > soft_deadline {
+            break;
+        }
+    }
+    best_move
+}
+
+/// Searches the root moves in order within `alpha..beta`, each against the best score so far.
+/// The best move is moved to the front and its line becomes the principal variation.
+/// Returns None once the search is stopped.
+fn search_root(depth: i16, mut alpha: i64, beta: i64, search: &mut Search) -> Option<i64> {
+    let roots = std::mem::take(&mut search.roots);
+    let mut best = None;
+    let mut line = vec![];
+    for (n, (mv, child, repeated)) in roots.iter().enumerate() {
+        line.clear();
+        let score = if *repeated {
+            0
+        }
+        else if n == 0 {
+            search.follow_pv = true;
+            -dhminimax(&mut child.clone(), depth-1, 1, -beta, -alpha, search, &mut line)
+        }
+        else {
+            let score = -dhminimax(&mut child.clone(), depth-1, 1, -alpha-1, -alpha, search, &mut line);
+            if score > alpha && score < beta {
+                line.clear();
+                -dhminimax(&mut child.clone(), depth-1, 1, -beta, -alpha, search, &mut line)
             }
             else {
-                best_move = Some((score, mv));
+                score
+            }
+        };
+        if search.stopped {
+            search.roots = roots;
+            return None;
+        }
+        if score > alpha {
+            alpha = score;
+            best = Some(n);
+            search.pv = std::iter::once(mv.clone()).chain(line.drain(..)).collect();
+            if alpha >= beta {
+                break;
             }
         }
-        eprintln!("Eval: {:?}", best_move.unwrap().0);
-        return best_move.unwrap().1;
     }
+    search.roots = roots;
+    if let Some(n) = best {
+        search.roots[..=n].rotate_right(1);
+    }
+    Some(alpha)
+}
+
+/// State of a search, kept across the iterations of `compute_move`.
+struct Search<'a> {
+    table: &'a TranspositionTable,
+    tablebase: Option<&'a Tablebase>,
+    /// Set when any search of the move should stop.
+    stop: &'a AtomicBool,
+    /// The moves at the root, the positions they lead to and whether those would repeat a third time, best first.
+    roots: Vec<(Move, Board, bool)>,
+    deadline: Instant,
+    nodes: u64,
+    stopped: bool,
+    /// Principal variation found by the last finished iteration, searched first by the next one.
+    pv: Vec<Move>,
+    /// Whether the node being entered is on `pv`.
+    follow_pv: bool,
+    /// Positions the current quiescence search may still expand.
+    quiescence_left: u32,
+    /// The last two quiet moves that caused a beta cutoff at each ply.
+    killers: Vec<[Option<Move>; 2]>,
+    /// How much quiet moves caused beta cutoffs, by `history_index`.
+    history: Vec<u32>
+}
+
+/// Index of a move in `Search::history`, from its start and end squares on a board of up to 10x10.
+fn history_index(mv: &Move) -> usize {
+    let square = |cp: CellPos| cp.row*10 + cp.col;
+    square(mv.from)*100 + square(mv.to())
 }
 
+impl<'a> Search<'a> {
+    fn new(table: &'a TranspositionTable, tablebase: Option<&'a Tablebase>, deadline: Instant, stop: &'a AtomicBool) -> Self {
+        Self {
+            table,
+            tablebase,
+            stop,
+            roots: vec![],
+            deadline,
+            nodes: 0,
+            stopped: false,
+            pv: vec![],
+            follow_pv: false,
+            quiescence_left: 0,
+            killers: vec![],
+            history: vec![0; 100*100]
+        }
+    }
+
+    /// Counts a visited position and returns whether the search should stop.
+    fn count_node(&mut self) -> bool {
+        self.nodes += 1;
+        if self.nodes & 1023 == 0 && Instant::now() >= self.deadline {
+            self.stop.store(true, Ordering::Relaxed);
+        }
+        if self.stop.load(Ordering::Relaxed) {
+            self.stopped = true;
+        }
+        self.stopped
+    }
+
+    /// Indices into `moves` in search order: `first`, then the captures taking the most pieces, the killers of `ply` and the quiet moves with the most history.
+    fn order_moves(&self, moves: &[Move], ply: usize, first: Option<usize>) -> Vec<usize> {
+        let killers = self.killers.get(ply);
+        let key = |i: usize| {
+            let mv = &moves[i];
+            if Some(i) == first {
+                u64::MAX
+            }
+            else if mv.is_capture() {
+                (3 << 32) + mv.captures.len() as u64
+            }
+            else if let Some(k) = killers.and_then(|killers| killers.iter().position(|killer| killer.as_ref() == Some(mv))) {
+                (2 << 32) - k as u64
+            }
+            else {
+                self.history[history_index(mv)] as u64
+            }
+        };
+        let mut order = (0..moves.len()).collect::<Vec<_>>();
+        order.sort_by_cached_key(|&i| std::cmp::Reverse(key(i)));
+        order
+    }
+
+    /// Remembers a quiet move that caused a beta cutoff at `ply` with `depth` plies left.
+    fn record_cutoff(&mut self, mv: &Move, ply: usize, depth: i16) {
+        if self.killers.len() <= ply {
+            self.killers.resize(ply+1, [None, None]);
+        }
+        let killers = &mut self.killers[ply];
+        if killers[0].as_ref() != Some(mv) {
+            killers[1] = killers[0].replace(mv.clone());
+        }
+        let depth = depth.max(1) as u32;
+        let score = &mut self.history[history_index(mv)];
+        *score = score.saturating_add(depth*depth);
+    }
+}
 
-fn nnminimax(mut board: Board, depth: u16) -> i64 {
+
+fn nnminimax(board: &mut Board, depth: u16) -> i64 {
     if depth >= 5 && board.must_jump.len() == 0 {
-        return heuristic(&board);
+        return heuristic(board);
     } 
-    let to_explore = if board.must_jump.len() != 0 {
-        board.must_jump.clone()
-    }
-    else {
-        board.piece_pos(board.turn)
-    }; 
-    
     let mut bst = LOST;
-    let old_board = board.clone();
-
-    for cp in to_explore {
-        for dir in DIRS {
-            let pm = PieceMove {pos: cp, dir};
-            if board.make_move(pm) {
-                let score = if board.turn == old_board.turn {
-                    nnminimax(board, depth+1)
-                }
-                else {
-                    -nnminimax(board, depth+1)
-                };
-                
 
-                if score > bst {
-                    bst = score;
-                }
+    for mv in board.legal_moves() {
+        let undo = board.apply_unchecked(&mv);
+        let score = -nnminimax(board, depth+1);
+        board.unmake_move(undo);
 
-                board = old_board.clone();
-            }
+        if score > bst {
+            bst = score;
         }
     }
     return bst;
 }
-fn nminimax(mut board: Board, depth: u16) -> i64 {
+fn nminimax(board: &mut Board, depth: u16) -> i64 {
     if depth >= 5 {
-        return simple_heuristic(&board);
+        return simple_heuristic(board);
     } 
-    let to_explore = if board.must_jump.len() != 0 {
-        board.must_jump.clone()
-    }
-    else {
-        board.piece_pos(board.turn)
-    }; 
-    
     let mut bst = LOST;
-    let old_board = board.clone();
-
-    for cp in to_explore {
-        for dir in DIRS {
-            let pm = PieceMove {pos: cp, dir};
-            if board.make_move(pm) {
-                let score = if board.turn == old_board.turn {
-                    nminimax(board, depth+1)
-                }
-                else {
-                    -nminimax(board, depth+1)
-                };
-                
 
-                if score > bst {
-                    bst = score;
-                }
+    for mv in board.legal_moves() {
+        let undo = board.apply_unchecked(&mv);
+        let score = -nminimax(board, depth+1);
+        board.unmake_move(undo);
 
-                board = old_board.clone();
-            }
+        if score > bst {
+            bst = score;
         }
     }
     return bst;
 }
 
-fn dhminimax(mut board: Board, depth: i16, mut alpha: i64, beta: i64, (transp_table, overwritten, utilized): (&mut HashMap<u64, (i64, i16, i64, i64)>, &mut u64, &mut u64)) -> i64 {
-    if let Some(&(teval, tdepth, talpha, tbeta)) = transp_table.get(&board.hash) {
-        if tdepth >= depth && talpha <= alpha && tbeta >= beta {
-            *utilized += 1;
-            return teval;
-        }
+/// Alpha-beta search of `board`; `pv` receives the best line found from it.
+/// Returns 0 without a useful score once `search.stopped` is set.
+fn dhminimax(board: &mut Board, depth: i16, ply: usize, mut alpha: i64, beta: i64, search: &mut Search, pv: &mut Vec<Move>) -> i64 {
+    let on_pv = std::mem::take(&mut search.follow_pv);
+    if search.count_node() {
+        return 0;
     }
-    if depth <= 0 && board.must_jump.len() == 0 {
-        return heuristic(&board);
-    } 
-    let to_explore = if board.must_jump.len() != 0 {
-        board.must_jump.clone()
+    if let Some(value) = search.tablebase.and_then(|tablebase| tablebase.probe(board)) {
+        return tablebase_score(value, board, ply);
     }
-    else {
-        board.piece_pos(board.turn)
-    }; 
+    if depth <= 0 {
+        search.quiescence_left = QUIESCENCE_NODES;
+        return quiescence(board, alpha, beta, search);
+    }
+    let entry = search.table.probe(board.hash);
+    if let Some(entry) = entry.filter(|entry| entry.depth as i16 >= depth) {
+        let score = score_from_table(entry.score, ply);
+        let usable = match entry.bound {
+            Bound::Exact => true,
+            Bound::Lower => score >= beta,
+            Bound::Upper => score <= alpha
+        };
+        if usable {
+            return score;
+        }
+    }
+    let to_explore = board.legal_moves();
 
+    // Losing later is better, so the loss counts the plies from the root.
     if to_explore.len() == 0 {
-        return LOST-depth as i64*100;
+        return LOST + ply as i64;
     }
-    
-    let old_board = board.clone();
 
-    // let to_explore = sort_by_heuristic(board.clone(), to_explore, heuristic);
+    let pv_move = search.pv.get(ply).filter(|_| on_pv);
+    let pv_index = pv_move.and_then(|pv_move| to_explore.iter().position(|mv| mv == pv_move));
+    let hash_index = entry.and_then(|entry| entry.best_move).map(usize::from).filter(|&i| i < to_explore.len());
+    // Indices into `to_explore`, which the table refers to.
+    let order = search.order_moves(&to_explore, ply, pv_index.or(hash_index));
+
     let old_alpha = alpha;
-    let old_beta = beta;
-
-    for cp in to_explore {
-        for dir in DIRS {
-            let pm = PieceMove {pos: cp, dir};
-            let ndepth = if board.must_jump.len() != 1 {depth-1} else {depth};
-            if board.make_move(pm) {
-                let score = if board.turn == old_board.turn {
-                    dhminimax(board, ndepth, alpha, beta, (transp_table, overwritten, utilized))
-                }
-                else {
-                    -dhminimax(board, ndepth, -beta, -alpha, (transp_table, overwritten, utilized))
-                };
-                
-
-                if score > alpha {
-                    alpha = score;
-                    if alpha >= beta {
-                        return alpha;
-                    }
-                }
+    let ndepth = depth-1;
+
+    let mut best_index = None;
+    let mut line = vec![];
+    for (n, i) in order.into_iter().enumerate() {
+        let mv = &to_explore[i];
+        let undo = board.apply_unchecked(mv);
+        search.follow_pv = n == 0 && pv_index == Some(i);
+        line.clear();
+        let score = if n == 0 {
+            -dhminimax(board, ndepth, ply+1, -beta, -alpha, search, &mut line)
+        }
+        else {
+            // Later moves only have to be shown no better than alpha, which a null window does faster.
+            let score = -dhminimax(board, ndepth, ply+1, -alpha-1, -alpha, search, &mut line);
+            if score > alpha && score < beta {
+                line.clear();
+                -dhminimax(board, ndepth, ply+1, -beta, -alpha, search, &mut line)
+            }
+            else {
+                score
+            }
+        };
+        board.unmake_move(undo);
+        if search.stopped {
+            return 0;
+        }
 
-                board = old_board.clone();
+        if score > alpha {
+            alpha = score;
+            best_index = Some(i);
+            pv.clear();
+            pv.push(mv.clone());
+            pv.append(&mut line);
+            if alpha >= beta {
+                if !mv.is_capture() {
+                    search.record_cutoff(mv, ply, depth);
+                }
+                break;
             }
         }
     }
 
-    // store with old_alpha and old_beta
-    if board.must_jump.len() != 1 && transp_table.insert(board.hash, (alpha, depth.max(0), old_alpha, old_beta)).is_some() {
-        *overwritten += 1;
+    let bound = if alpha >= beta {
+        Bound::Lower
+    }
+    else if alpha > old_alpha {
+        Bound::Exact
     }
+    else {
+        Bound::Upper
+    };
+    search.table.store(board.hash, TableEntry {
+        score: score_to_table(alpha, ply),
+        depth: depth as u8,
+        bound,
+        best_move: best_index.map(|i| i as u16)
+    });
 
     return alpha;
 }
 
-fn dminimax(mut board: Board, depth: u16, mut alpha: i64, beta: i64) -> i64 {
+fn dminimax(board: &mut Board, depth: u16, mut alpha: i64, beta: i64) -> i64 {
     if depth >= 8 && board.must_jump.len() == 0 {
-        return heuristic(&board);
+        return heuristic(board);
     } 
-    let to_explore = if board.must_jump.len() != 0 {
-        board.must_jump.clone()
-    }
-    else {
-        board.piece_pos(board.turn)
-    }; 
-    
-    let old_board = board.clone();
-
-    // let to_explore = sort_by_heuristic(board.clone(), to_explore, heuristic);
-
-    for cp in to_explore {
-        for dir in DIRS {
-            let pm = PieceMove {pos: cp, dir};
-            let ndepth = if board.must_jump.len() != 1 {depth+1} else {depth};
-            if board.make_move(pm) {
-                let score = if board.turn == old_board.turn {
-                    dminimax(board, ndepth, alpha, beta)
-                }
-                else {
-                    -dminimax(board, ndepth, -beta, -alpha)
-                };
-                
-
-                if score > alpha {
-                    alpha = score;
-                    if alpha >= beta {
-                        return alpha;
-                    }
-                }
-
-                board = old_board.clone();
+    // let to_explore = sort_by_heuristic(board, to_explore, heuristic);
+    let ndepth = if board.must_jump.len() != 1 {depth+1} else {depth};
+
+    for mv in board.legal_moves() {
+        let undo = board.apply_unchecked(&mv);
+        let score = -dminimax(board, ndepth, -beta, -alpha);
+        board.unmake_move(undo);
+
+        if score > alpha {
+            alpha = score;
+            if alpha >= beta {
+                return alpha;
             }
         }
     }
@@ -243,16 +481,89 @@ fn dminimax(mut board: Board, depth: u16, mut alpha: i64, beta: i64) -> i64 {
 
 const MAX_COMPUTE: i64 = 1_000_000;
 
-fn evaluate(board: Board) -> i64 {
-    let mut overwritten = 0;
-    let mut utilized = 0;
-    // eprintln!("Board hash: {}", board.hash);
-    let eval = dhminimax(board, 9, LOST, WIN, (&mut Default::default(), &mut overwritten, &mut utilized));
-    // eprintln!("Overwritten: {}; utilized: {}", overwritten, utilized);
-    return eval;
+/// Score of a tablebase result for `board`, `ply` plies from the root: below the wins the search finds itself, sooner wins higher.
+/// Wins of unknown length score below the known ones, the higher the better the winner's position looks
+/// so that the search still makes progress.
+fn tablebase_score(value: TablebaseValue, board: &Board, ply: usize) -> i64 {
+    let unknown = |sign: i64| UNKNOWN_DISTANCE - sign * heuristic(board).clamp(-UNKNOWN_DISTANCE/2, UNKNOWN_DISTANCE/2);
+    match value {
+        TablebaseValue::Win(d) => TABLEBASE_WIN - (ply as i64 + d.map_or_else(|| unknown(1), i64::from)),
+        TablebaseValue::Loss(d) => -TABLEBASE_WIN + (ply as i64 + d.map_or_else(|| unknown(-1), i64::from)),
+        TablebaseValue::Draw => 0
+    }
+}
+
+/// Counted as the distance of a win or loss the tablebase doesn't know the length of, beyond any it stores.
+const UNKNOWN_DISTANCE: i64 = 1000;
+
+const TABLEBASE_WIN: i64 = WIN / 2;
+
+/// Scores at least this far from a draw are wins or losses, which depend on the plies from the root.
+const DISTANCE_SCORE: i64 = TABLEBASE_WIN / 2;
+
+/// `score` of a node `ply` plies from the root, as stored in the table: counting the plies from the node itself,
+/// so that the entry holds wherever the position is reached again.
+fn score_to_table(score: i64, ply: usize) -> i64 {
+    if score >= DISTANCE_SCORE {
+        score + ply as i64
+    }
+    else if score <= -DISTANCE_SCORE {
+        score - ply as i64
+    }
+    else {
+        score
+    }
 }
 
+/// The inverse of `score_to_table`.
+fn score_from_table(score: i64, ply: usize) -> i64 {
+    if score >= DISTANCE_SCORE {
+        score - ply as i64
+    }
+    else if score <= -DISTANCE_SCORE {
+        score + ply as i64
+    }
+    else {
+        score
+    }
+}
+
+/// Plays out the captures pending at a leaf, so that `heuristic` is never taken in the middle of an exchange.
+/// Captures are compulsory, so every one is searched; a quiet position is evaluated as it stands.
+fn quiescence(board: &mut Board, mut alpha: i64, beta: i64, search: &mut Search) -> i64 {
+    if board.must_jump.is_empty() || search.quiescence_left == 0 {
+        return heuristic(board);
+    }
+    search.quiescence_left -= 1;
+    let mut captures = board.legal_moves();
+    captures.sort_by_key(|mv| std::cmp::Reverse(mv.captures.len()));
+    for mv in captures {
+        let undo = board.apply_unchecked(&mv);
+        let stopped = search.count_node();
+        let score = if stopped { 0 } else { -quiescence(board, -beta, -alpha, search) };
+        board.unmake_move(undo);
+        if search.stopped {
+            return 0;
+        }
+        if score > alpha {
+            alpha = score;
+            if alpha >= beta {
+                break;
+            }
+        }
+    }
+    alpha
+}
+
+/// Most positions a quiescence search visits before it evaluates the rest as they stand.
+const QUIESCENCE_NODES: u32 = 1000;
+
+/// Half-width of the first window around the previous iteration's score.
+const ASPIRATION_WINDOW: i64 = 10;
+
+/// Deepest iteration of `compute_move`.
+const MAX_DEPTH: i16 = 64;
+
 fn simple_heuristic(board: &Board) -> i64 {
     return board.piece_pos(board.turn).len() as i64 - board.piece_pos(-board.turn).len() as i64;
 }
-
@@ -0,0 +1,95 @@
+use std::time::{Duration, Instant};
+
+use checkers::*;
+use rand::{thread_rng, seq::SliceRandom};
+
+fn usage(program: &str) -> ! {
+    eprintln!("Usage: {} <output file> [--rules <name>] [--plies <n>] [--min-games <n>] [--pdn <file>]... \
+        [--self-play <games> [--time <ms>] [--depth <n>] [--random <plies>]]", program);
+    std::process::exit(2);
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let program = &args[0];
+
+    let mut output = None;
+    let mut rules = RuleSet::default();
+    // Positions deeper into the game than this are left out of the book.
+    let mut plies = 16;
+    let mut min_games = 2;
+    let mut pdn_files = vec![];
+    let mut self_play = 0;
+    let mut budget = Duration::from_millis(100);
+    let mut depth = None;
+    // Self-play games start with this many random moves, or the searches would play the same game every time.
+    let mut random_plies = 4;
+
+    let mut rest = args.iter().skip(1);
+    while let Some(arg) = rest.next() {
+        let mut value = || rest.next().unwrap_or_else(|| usage(program));
+        match arg.as_str() {
+            "--rules" => rules = value().parse().unwrap_or_else(|_| usage(program)),
+            "--plies" => plies = value().parse().unwrap_or_else(|_| usage(program)),
+            "--min-games" => min_games = value().parse().unwrap_or_else(|_| usage(program)),
+            "--pdn" => pdn_files.push(value().clone()),
+            "--self-play" => self_play = value().parse().unwrap_or_else(|_| usage(program)),
+            "--time" => budget = Duration::from_millis(value().parse().unwrap_or_else(|_| usage(program))),
+            "--depth" => depth = Some(value().parse().unwrap_or_else(|_| usage(program))),
+            "--random" => random_plies = value().parse().unwrap_or_else(|_| usage(program)),
+            _ if output.is_none() => output = Some(arg.clone()),
+            _ => usage(program)
+        }
+    }
+    let Some(output) = output else {
+        usage(program);
+    };
+
+    let start = Instant::now();
+    let mut book = OpeningBook::new(rules);
+    for path in &pdn_files {
+        let games = std::fs::read_to_string(path).map_err(ParseError::from).and_then(|text| pdn::parse_games(&text));
+        let games = match games {
+            Ok(games) => games,
+            Err(err) => {
+                eprintln!("Failed to read {}: {}", path, err);
+                std::process::exit(1);
+            }
+        };
+        let added = games.iter().filter(|game| book.add_game(game, plies)).count();
+        println!("{}: {} of {} games added", path, added, games.len());
+    }
+
+    let mut ai = AI::new();
+    if let Some(depth) = depth {
+        ai.set_max_depth(depth);
+    }
+    let mut rng = thread_rng();
+    for n in 0..self_play {
+        let mut game = Game::with_rules(rules);
+        while game.outcome().is_none() {
+            let board = game.board().clone();
+            let record = game.record();
+            let mv = if record.ply() < random_plies {
+                board.legal_moves().choose(&mut rng).cloned()
+            }
+            else {
+                ai.set_history((0..=record.ply()).filter_map(|ply| record.hash_at(ply)).collect());
+                ai.compute_move(&board, budget)
+            };
+            let Some(mv) = mv else {
+                break;
+            };
+            game.play(&mv);
+        }
+        book.add_game(&PdnGame::from_game(&game), plies);
+        println!("Self-play game {}: {} after {} plies ({:.1}s)", n+1, game.outcome().unwrap(), game.record().ply(), start.elapsed().as_secs_f64());
+    }
+
+    book.prune(min_games);
+    if let Err(err) = book.save(&output) {
+        eprintln!("Failed to write {}: {}", output, err);
+        std::process::exit(1);
+    }
+    println!("Wrote {} positions to {} in {:.1}s", book.len(), output, start.elapsed().as_secs_f64());
+}
@@ -3,17 +3,25 @@ use rand::Rng;
 fn main() {
     let mut rng = rand::thread_rng();
     
-    let mut board_hash: [[u64; 5]; 32] = [[0; 5]; 32];
-    for i in 0..32 {
+    // One row per dark square of the largest (10x10) board.
+    let mut board_hash: [[u64; 5]; 50] = [[0; 5]; 50];
+    for i in 0..50 {
         for j in 0..5 {
             board_hash[i][j] = rng.gen();
         }
     }
     
+    // Pieces already jumped but not yet removed, so positions in the middle of a capture hash apart.
+    let mut captured_hash: [u64; 50] = [0; 50];
+    for hash in &mut captured_hash {
+        *hash = rng.gen();
+    }
+
     let white_turn_hash: u64 = rng.gen();
     let black_turn_hash: u64 = rng.gen();
     
     println!("{:?}", board_hash);
+    println!("{:?}", captured_hash);
 
     println!("White Turn Hash: {}", white_turn_hash);
     println!("Black Turn Hash: {}", black_turn_hash);
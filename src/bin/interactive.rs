@@ -4,7 +4,7 @@
 
 use std::{sync::{RwLock, Arc}, thread::JoinHandle, io::stdin, f32::consts::E};
 
-use checkers::{Board, CellPos, Color, MoveDir, PieceMove, DIRS, cell};
+use checkers::{Board, CellPos, Color, Notation, PieceMove, RuleSet, cell};
 use ellipsoid::prelude::{*, winit::event::{ElementState, MouseButton}};
 use strum::{Display, EnumIter};
 
@@ -12,7 +12,7 @@ type Txts = CheckersTextures;
 
 struct Checkers {
     graphics: Graphics<Txts>,
-    game_data: Arc<RwLock<(Color, Board)>>,
+    game_data: Arc<RwLock<(Color, Board, Option<RuleSet>, Notation)>>,
     receiver_handle: JoinHandle<()>,
     selected_square: Option<CellPos>,
     mouse_pos: Vec2,
@@ -36,7 +36,7 @@ impl Textures for CheckersTextures {}
 impl App<Txts> for Checkers {
     async fn new(window: winit::window::Window) -> Self {
         let graphics = Graphics::<Txts>::new(window).await;
-        let game_data = Arc::new(RwLock::new((Color::White, Board::new())));
+        let game_data = Arc::new(RwLock::new((Color::White, Board::new(), None, Notation::Algebraic)));
         let gdc = game_data.clone();
         let receiver_handle = std::thread::spawn(|| {
             receiver(gdc);
@@ -54,27 +54,42 @@ impl App<Txts> for Checkers {
     }
 
     fn draw(&mut self) {
-        let (color, mut board) = self.game_data.read().unwrap().clone();
+        let (color, board, rules, notation) = self.game_data.read().unwrap().clone();
         if let Some(cp) = self.selected_square && board.turn == color {
             egui::Window::new("Move").show(&self.graphics.egui_platform.context(), |ui| {
-                let old_board = board.clone();
-                let valid_dirs = DIRS.into_iter().filter(|&dir| {
-                    if board.make_move(PieceMove {pos: cp, dir}) {
-                        board = old_board.clone();
-                        true
+                let moves = board.legal_moves().into_iter().filter(|mv| mv.from == cp).collect::<Vec<_>>();
+                let mut valid_dirs = vec![];
+                for mv in &moves {
+                    let dir = mv.steps()[0].dir;
+                    if !valid_dirs.contains(&dir) {
+                        valid_dirs.push(dir);
                     }
-                    else {
-                        false
-                    }
-                }).collect::<Vec<_>>();
+                }
                 if valid_dirs.is_empty() {
                     ui.label("No available moves.");
                 }
+                else if notation == Notation::Numeric {
+                    for mv in moves {
+                        let numeric = board.numeric_move(&mv);
+                        if ui.button(&numeric).clicked() {
+                            assert!(self.game_data.write().unwrap().1.apply(&mv).is_some());
+                            println!("{}", numeric);
+                        }
+                    }
+                }
+                else if rules.is_some() {
+                    for mv in moves {
+                        if ui.button(format!("{}", mv)).clicked() {
+                            assert!(self.game_data.write().unwrap().1.apply(&mv).is_some());
+                            println!("{}", mv);
+                        }
+                    }
+                }
                 else {
                     for dir in valid_dirs {
                         if ui.button(format!("{}", dir)).clicked() {
                             let pm = PieceMove {pos: cp, dir};
-                            assert!(self.game_data.write().unwrap().1.make_move(pm));
+                            assert!(self.game_data.write().unwrap().1.make_move(pm).is_some());
                             println!("{}", pm);
                         }
                     }
@@ -85,10 +100,12 @@ impl App<Txts> for Checkers {
 
         let board_gt = GTransform::from_translation(Vec2::NEG_ONE).inflate(2.);
 
-        for i in 0..8 {
-            for j in 0..8 {
+        let size = board.size();
+        let cell_len = 1. / size as f32;
+        for i in 0..size {
+            for j in 0..size {
                 let mut cell_shape = Shape::from_square().set_color(if (i+j)%2 == 0 { ellipsoid::Color::from_hex(0x964d37) } else {ellipsoid::Color::from_hex(0xdad9b5)}).set_z(0.9);
-                let cell_gt = GTransform::from_translation(Vec2::new(j as f32*1./8., i as f32*1./8.)).inflate(1./8.);
+                let cell_gt = GTransform::from_translation(Vec2::new(j as f32*cell_len, i as f32*cell_len)).inflate(cell_len);
 
                 if let Some(cp) = self.selected_square {
                     if cp == cell(i, j) {
@@ -131,10 +148,11 @@ impl App<Txts> for Checkers {
             }
             WindowEvent::MouseInput {state, button, ..} => {
                 if *state == ElementState::Pressed && *button == MouseButton::Right {
-                    let row = (self.mouse_pos.y * 8.) as usize;                    
-                    let col = (self.mouse_pos.x * 8.) as usize;
+                    let size = self.game_data.read().unwrap().1.size();
+                    let row = (self.mouse_pos.y * size as f32) as usize;                    
+                    let col = (self.mouse_pos.x * size as f32) as usize;
 
-                    if row < 8 && col < 8 {
+                    if row < size && col < size {
                         self.selected_square = Some(cell(row, col));
                     }
                 }
@@ -158,30 +176,65 @@ fn main() {
     async_std::task::block_on(start());
 }
 
-fn receiver(game_data: Arc<RwLock<(Color, Board)>>) {
+fn receiver(game_data: Arc<RwLock<(Color, Board, Option<RuleSet>, Notation)>>) {
     let stdin = stdin();
 
     loop {
         let mut inp = String::new();
-        stdin.read_line(&mut inp).unwrap();
+        if stdin.read_line(&mut inp).unwrap() == 0 {
+            break;
+        }
         inp = inp.trim().into();
-        if inp == "white" || inp == "black" {
-            game_data.write().unwrap().0 = Color::from_str(&inp);
+        if let Ok(color) = inp.parse::<Color>() {
+            game_data.write().unwrap().0 = color;
             continue;
         }
         if inp == "exit" {
             break;
         }
-        let must_jump = inp.split_whitespace().map(|pstr| {
-            CellPos::from_str(pstr)
-        }).collect::<Vec<_>>();
+        if let Some(name) = inp.strip_prefix("rules ") {
+            match name.parse::<RuleSet>() {
+                Ok(rules) => game_data.write().unwrap().2 = Some(rules),
+                Err(err) => eprintln!("Ignoring command: {}", err)
+            }
+            continue;
+        }
+        if let Some(name) = inp.strip_prefix("notation ") {
+            match name.parse::<Notation>() {
+                Ok(notation) => game_data.write().unwrap().3 = notation,
+                Err(err) => eprintln!("Ignoring command: {}", err)
+            }
+            continue;
+        }
 
         // eprintln!("Reading board from stdin...");
-        let board = Board::from_stdin(&stdin);
+        let (notation, rules) = {
+            let game_data = game_data.read().unwrap();
+            (game_data.3, game_data.2)
+        };
+        let board = match rules {
+            Some(rules) => Board::read_with_rules(&mut stdin.lock(), rules),
+            None => Board::read_from(&mut stdin.lock())
+        };
+        let must_jump = board.as_ref().map_or(Ok(vec![]), |board| inp.split_whitespace().map(|pstr| {
+            board.parse_square(pstr, notation)
+        }).collect::<Result<Vec<_>, _>>());
+        let (mut board, must_jump) = match (board, must_jump) {
+            (Ok(board), Ok(must_jump)) => (board, must_jump),
+            (Err(err), _) | (_, Err(err)) => {
+                println!("error {}", err);
+                continue;
+            }
+        };
         let mut game_data = game_data.write().unwrap();
+        board.turn = game_data.0;
+        board.must_jump = must_jump;
+        board.recompute_hash();
+        if let Err(errors) = board.validate() {
+            println!("error {}", checkers::describe_errors(&errors));
+            continue;
+        }
         game_data.1 = board;
-        game_data.1.turn = game_data.0;
-        game_data.1.must_jump = must_jump;
     }
 }
 
@@ -0,0 +1,56 @@
+use std::time::Instant;
+
+use checkers::*;
+
+fn usage(program: &str) -> ! {
+    eprintln!("Usage: {} <depth> [--divide] [--threads <n>] [--rules <name>] [--fen <fen>]", program);
+    std::process::exit(2);
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let program = &args[0];
+
+    let mut depth = None;
+    let mut show_divide = false;
+    let mut threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut rules = RuleSet::American;
+    let mut fen = None;
+
+    let mut rest = args.iter().skip(1);
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--divide" => show_divide = true,
+            "--threads" => threads = rest.next().and_then(|n| n.parse().ok()).unwrap_or_else(|| usage(program)),
+            "--rules" => rules = rest.next().and_then(|name| name.parse().ok()).unwrap_or_else(|| usage(program)),
+            "--fen" => fen = Some(rest.next().unwrap_or_else(|| usage(program)).clone()),
+            _ => depth = Some(arg.parse::<u32>().unwrap_or_else(|_| usage(program)))
+        }
+    }
+    let Some(depth) = depth else {
+        usage(program);
+    };
+
+    let board = match fen {
+        Some(fen) => Board::from_fen(&fen, rules).unwrap_or_else(|err| {
+            eprintln!("Invalid position: {}", err);
+            std::process::exit(2);
+        }),
+        None => Board::with_rules(rules)
+    };
+    println!("{}", board);
+
+    let start = Instant::now();
+    let nodes = if show_divide {
+        let counts = perft::divide_parallel(&board, depth, threads);
+        for (mv, nodes) in &counts {
+            println!("{}: {}", board.numeric_move(mv), nodes);
+        }
+        counts.iter().map(|(_, nodes)| nodes).sum()
+    }
+    else {
+        perft::perft_parallel(&board, depth, threads)
+    };
+    let elapsed = start.elapsed();
+    println!("perft({}) = {} in {:.3}s ({:.0} nodes/s)", depth, nodes, elapsed.as_secs_f64(), nodes as f64 / elapsed.as_secs_f64().max(1e-9));
+}
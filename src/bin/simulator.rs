@@ -16,7 +16,18 @@ fn main() {
     let ai1_path = "/home/patrik/Code/Games/checkers/target/release/interactive";
     let ai2_path = "/home/patrik/Code/Games/checkers/target/release/checkers";
 
-    let mut board = Board::new();
+    // Engines are only told about the rules (and answer with whole moves) when they are not the standard ones.
+    let rules = match args.get(1) {
+        Some(name) => name.parse::<RuleSet>().expect("Unknown rule set"),
+        None => RuleSet::Standard
+    };
+
+    // Finished games are appended here as PDN when a second argument is given.
+    let mut pdn_file = args.get(2).map(|path| {
+        std::fs::OpenOptions::new().create(true).append(true).open(path).expect("Failed to open PDN file")
+    });
+
+    let mut game = Game::with_rules(rules);
 
     let mut ai1_child = Command::new(ai1_path)
         .stdin(Stdio::piped())
@@ -40,6 +51,10 @@ fn main() {
     
     writeln!(ai1_stdin, "{}", ai1_color).unwrap();
     writeln!(ai2_stdin, "{}", ai2_color).unwrap();
+    if rules != RuleSet::Standard {
+        writeln!(ai1_stdin, "rules {}", rules).unwrap();
+        writeln!(ai2_stdin, "rules {}", rules).unwrap();
+    }
 
     let mut wins1 = 0;
     let mut wins2 = 0;
@@ -47,35 +62,40 @@ fn main() {
 
     let mut evals = vec![];
 
-    const MAX_MOVES: u32 = 200;
-
     let mut cnt_moves = 0;
     let mut cgame = 0;
     let games_total = 30;
 
     let mut time1 = 0;
     let mut time2 = 0;
+    // The winner of a game the other engine gave up by resigning or rejecting the position.
+    let mut forfeit: Option<Color> = None;
 
 
     while cgame != games_total {
+        let board = game.board();
         println!("{}", board);
         println!("{}", board.turn);
         println!("move: {}", cnt_moves);
 
-        if !board.exists_valid_move() || cnt_moves > MAX_MOVES {
-
-            if cnt_moves > MAX_MOVES {
-                ties += 1;
+        if let Some(outcome) = forfeit.take().map(Outcome::Win).or_else(|| game.outcome()) {
+            println!("{}", outcome);
+            match outcome {
+                Outcome::Draw(_) => ties += 1,
+                Outcome::Win(color) if color == ai1_color => wins1 += 1,
+                Outcome::Win(_) => wins2 += 1
             }
-            else {
-                if board.turn == ai1_color {
-                    wins2 += 1;
-                }
-                else {
-                    wins1 += 1;
-                }
+            if let Some(file) = &mut pdn_file {
+                let mut pdn = PdnGame::from_game(&game);
+                pdn.result = Some(outcome).into();
+                pdn.set_tag("Event", "Simulator");
+                pdn.set_tag("Round", &(cgame+1).to_string());
+                let (black, white) = if ai1_color == Color::Black { (ai1_path, ai2_path) } else { (ai2_path, ai1_path) };
+                pdn.set_tag("Black", black);
+                pdn.set_tag("White", white);
+                writeln!(file, "{}", pdn).unwrap();
             }
-            let heur = heuristic(&board);
+            let heur = heuristic(board);
             if board.turn == ai1_color {
                 evals.push(heur);
             }
@@ -88,8 +108,9 @@ fn main() {
             ai2_color = -ai2_color;
             writeln!(ai1_stdin, "{}", ai1_color).unwrap();
             writeln!(ai2_stdin, "{}", ai2_color).unwrap();
-            board = Board::new();
+            game = Game::with_rules(rules);
         }
+        let board = game.board();
 
         let (stdin, stdout, time) = if board.turn == ai1_color {
             (&mut ai1_stdin, &mut ai1_stdout, &mut time1)
@@ -99,6 +120,11 @@ fn main() {
 
         cnt_moves += 1;        
 
+        if let Err(errors) = board.validate() {
+            println!("Game reached an invalid position: {}", describe_errors(&errors));
+            break;
+        }
+
         for cp in &board.must_jump {
             write!(stdin, "{} ", cp).expect("Failed to write to stdin of ai1.");
         }
@@ -117,11 +143,38 @@ fn main() {
 
 
         ai_out = ai_out.trim().to_string();
-        let mv = PieceMove::from_str(&ai_out);
+        let ai = if board.turn==ai1_color { 1 } else { 2 };
+        if let Some(reason) = ai_out.strip_prefix("error ") {
+            println!("AI {} rejected the position: {} (loser).", ai, reason);
+            forfeit = Some(-board.turn);
+            continue;
+        }
+        if ai_out == "resign" {
+            println!("AI {} resigned (loser).", ai);
+            forfeit = Some(-board.turn);
+            continue;
+        }
+        // Engines answer with a step (`C2 tr`), a whole move (`C2xE4`) or a whole move in numeric notation (`22x15x8`).
+        let played = if rules == RuleSet::Standard {
+            match ai_out.parse::<PieceMove>() {
+                Ok(mv) => Ok(game.play_step(mv)),
+                Err(err) => PieceMove::parse_numeric(&ai_out).map(|steps| steps.into_iter().all(|mv| game.play_step(mv))).map_err(|_| err)
+            }
+        }
+        else {
+            board.parse_move(&ai_out).or_else(|err| board.parse_numeric_move(&ai_out).map_err(|_| err)).map(|mv| game.play(&mv))
+        };
 
-        if !board.make_move(mv) {
-            println!("AI {} made invalid move {} (loser).", if board.turn==ai1_color { 1 } else { 2 }, mv);
-            break;
+        match played {
+            Ok(true) => {}
+            Ok(false) => {
+                println!("AI {} made invalid move {} (loser).", ai, ai_out);
+                break;
+            }
+            Err(err) => {
+                println!("AI {} sent unreadable move {:?}: {} (loser).", ai, ai_out, err);
+                break;
+            }
         }
     }
     writeln!(ai1_stdin, "exit").unwrap();
@@ -130,5 +183,6 @@ fn main() {
     evals.sort();
     println!("{:?}", evals);
 
-    println!("AI1 wins: {}; AI2 wins: {}; ties: {}; AI1 time: {}; AI2 time: {}; median eval: {}", wins1, wins2, ties, time1/games_total, time2/games_total, evals[evals.len()/2]);
+    println!("AI1 wins: {}; AI2 wins: {}; ties: {}; AI1 time: {}; AI2 time: {}; median eval: {}", wins1, wins2, ties, time1/games_total, time2/games_total,
+        evals.get(evals.len()/2).map_or("none".to_string(), |eval| eval.to_string()));
 }
@@ -0,0 +1,61 @@
+use std::time::Instant;
+
+use checkers::*;
+
+fn usage(program: &str) -> ! {
+    eprintln!("Usage: {} <pieces> <output file> [--rules <name>] [--no-distances]", program);
+    std::process::exit(2);
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let program = &args[0];
+
+    let mut pieces = None;
+    let mut output = None;
+    let mut rules = RuleSet::American;
+    let mut distances = true;
+
+    let mut rest = args.iter().skip(1);
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--rules" => rules = rest.next().and_then(|name| name.parse().ok()).unwrap_or_else(|| usage(program)),
+            "--no-distances" => distances = false,
+            _ if pieces.is_none() => pieces = Some(arg.parse::<u32>().unwrap_or_else(|_| usage(program))),
+            _ if output.is_none() => output = Some(arg.clone()),
+            _ => usage(program)
+        }
+    }
+    let (Some(pieces), Some(output)) = (pieces, output) else {
+        usage(program);
+    };
+    if rules.size() != 8 || !(2..=tablebase::MAX_PIECES).contains(&pieces) {
+        eprintln!("Tablebases are generated for 2 to {} pieces on 8x8 boards.", tablebase::MAX_PIECES);
+        std::process::exit(2);
+    }
+
+    let start = Instant::now();
+    let tablebase = Tablebase::generate(rules, pieces, |material, table| {
+        let (mut wins, mut losses, mut longest) = (0, 0, 0);
+        for &entry in table.iter().filter(|&&entry| entry != 0) {
+            if entry%2 == 1 { losses += 1 } else { wins += 1 }
+            longest = longest.max(entry - 1);
+        }
+        println!("{:>8}: {} wins, {} losses, longest {} plies ({:.1}s)", material, wins, losses, longest, start.elapsed().as_secs_f64());
+    });
+    let mut tablebase = match tablebase {
+        Ok(tablebase) => tablebase,
+        Err(err) => {
+            eprintln!("Failed to generate the tablebase: {}", err);
+            std::process::exit(1);
+        }
+    };
+    if !distances {
+        tablebase.drop_distances();
+    }
+    if let Err(err) = tablebase.save(&output) {
+        eprintln!("Failed to write {}: {}", output, err);
+        std::process::exit(1);
+    }
+    println!("Wrote {} in {:.1}s", output, start.elapsed().as_secs_f64());
+}
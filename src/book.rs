@@ -0,0 +1,163 @@
+use super::*;
+use std::{collections::HashMap, io::{self, Read, Write}, path::Path};
+
+use rand::{Rng, distributions::WeightedIndex, prelude::Distribution};
+
+/// Results of the games that reached a position, for the side that moved into it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BookEntry {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32
+}
+
+impl BookEntry {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Points scored in these games, counting a draw as one and a win as two.
+    pub fn points(&self) -> u32 {
+        2*self.wins + self.draws
+    }
+}
+
+/// Game statistics of opening positions, keyed by `Board.hash`.
+///
+/// A move is in book when the position it leads to is; keying the positions
+/// rather than the moves lets transpositions share their statistics.
+pub struct OpeningBook {
+    rules: RuleSet,
+    entries: HashMap<u64, BookEntry>
+}
+
+const MAGIC: &[u8; 5] = b"CKOB1";
+
+impl OpeningBook {
+    pub fn new(rules: RuleSet) -> Self {
+        Self {
+            rules,
+            entries: HashMap::new()
+        }
+    }
+
+    pub fn rules(&self) -> RuleSet {
+        self.rules
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, hash: u64) -> Option<BookEntry> {
+        self.entries.get(&hash).copied()
+    }
+
+    /// Counts the result of `game` for the positions after each of its first `plies` moves.
+    /// Returns false, adding nothing, for an unfinished game or one under other rules.
+    pub fn add_game(&mut self, game: &PdnGame, plies: usize) -> bool {
+        if game.start.rules() != self.rules || game.result == PdnResult::Unfinished {
+            return false;
+        }
+        let mut board = game.start.clone();
+        for mv in game.moves.iter().take(plies) {
+            let mover = board.turn;
+            board.apply_unchecked(mv);
+            let entry = self.entries.entry(board.hash).or_default();
+            match game.result {
+                PdnResult::Win(color) if color == mover => entry.wins += 1,
+                PdnResult::Win(_) => entry.losses += 1,
+                _ => entry.draws += 1
+            }
+        }
+        true
+    }
+
+    /// Drops the positions reached in fewer than `min_games` games.
+    pub fn prune(&mut self, min_games: u32) {
+        self.entries.retain(|_, entry| entry.games() >= min_games);
+    }
+
+    /// The legal moves of `board` that lead to a book position, with its statistics.
+    pub fn moves(&self, board: &Board) -> Vec<(Move, BookEntry)> {
+        if board.rules() != self.rules {
+            return vec![];
+        }
+        board.legal_moves().into_iter().filter_map(|mv| {
+            let mut child = board.clone();
+            child.apply_unchecked(&mv);
+            Some((mv, self.get(child.hash)?))
+        }).collect()
+    }
+
+    /// A book move picked with probability proportional to its points, or None when no book move scored any.
+    pub fn pick_move(&self, board: &Board, rng: &mut impl Rng) -> Option<Move> {
+        let mut moves = self.moves(board);
+        let weights = WeightedIndex::new(moves.iter().map(|(_, entry)| entry.points())).ok()?;
+        Some(moves.swap_remove(weights.sample(rng)).0)
+    }
+
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        let name = self.rules.to_string();
+        writer.write_all(&[name.len() as u8])?;
+        writer.write_all(name.as_bytes())?;
+        writer.write_all(&(self.entries.len() as u64).to_le_bytes())?;
+        // Sorted so the same book is always written the same way.
+        let mut entries = self.entries.iter().collect::<Vec<_>>();
+        entries.sort_unstable_by_key(|&(&hash, _)| hash);
+        for (hash, entry) in entries {
+            writer.write_all(&hash.to_le_bytes())?;
+            for count in [entry.wins, entry.draws, entry.losses] {
+                writer.write_all(&count.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read_from(reader: &mut impl Read) -> io::Result<Self> {
+        let invalid = |what: &str| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid opening book: {}", what));
+        let mut magic = [0; 5];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("unknown format"));
+        }
+        let mut len = [0; 1];
+        reader.read_exact(&mut len)?;
+        let mut name = vec![0; len[0] as usize];
+        reader.read_exact(&mut name)?;
+        let rules = String::from_utf8(name).ok().and_then(|name| name.parse::<RuleSet>().ok()).ok_or_else(|| invalid("unknown rules"))?;
+
+        let mut word = [0; 8];
+        reader.read_exact(&mut word)?;
+        let count = u64::from_le_bytes(word);
+        let mut book = Self::new(rules);
+        for _ in 0..count {
+            reader.read_exact(&mut word)?;
+            let hash = u64::from_le_bytes(word);
+            let mut counts = [0; 3];
+            for count in &mut counts {
+                let mut bytes = [0; 4];
+                reader.read_exact(&mut bytes)?;
+                *count = u32::from_le_bytes(bytes);
+            }
+            let [wins, draws, losses] = counts;
+            book.entries.insert(hash, BookEntry { wins, draws, losses });
+        }
+        Ok(book)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = io::BufWriter::new(std::fs::File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read_from(&mut io::BufReader::new(std::fs::File::open(path)?))
+    }
+}
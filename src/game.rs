@@ -0,0 +1,129 @@
+use super::*;
+
+/// Plies without a capture or an uncrowned piece moving after which the game is drawn (ACF: 40 moves per side).
+pub const NO_PROGRESS_PLIES: usize = 80;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DrawReason {
+    Repetition,
+    NoProgress
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Win(Color),
+    Draw(DrawReason)
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Win(color) => write!(f, "{} wins", color),
+            Outcome::Draw(DrawReason::Repetition) => write!(f, "draw by threefold repetition"),
+            Outcome::Draw(DrawReason::NoProgress) => write!(f, "draw by the {}-move rule", NO_PROGRESS_PLIES/2)
+        }
+    }
+}
+
+/// A `Board` together with the history needed to decide when the game is over.
+#[derive(Clone)]
+pub struct Game {
+    /// The current position, which is ahead of the record while a capture is played step by step.
+    board: Board,
+    record: GameRecord,
+    /// Ply of the last capture or man move; no earlier position can occur again.
+    progress_ply: usize
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Game {
+    pub fn new() -> Self {
+        Self::from_board(Board::new())
+    }
+
+    pub fn with_rules(rules: RuleSet) -> Self {
+        Self::from_board(Board::with_rules(rules))
+    }
+
+    pub fn from_board(board: Board) -> Self {
+        Self {
+            record: GameRecord::new(board.clone()),
+            board,
+            progress_ply: 0
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn record(&self) -> &GameRecord {
+        &self.record
+    }
+
+    pub fn start(&self) -> &Board {
+        self.record.start()
+    }
+
+    /// Every completed move so far.
+    pub fn moves(&self) -> &[Move] {
+        self.record.moves()
+    }
+
+    /// Plays a whole move; returns false and leaves the game untouched if it is illegal.
+    pub fn play(&mut self, mv: &Move) -> bool {
+        let turn = self.board.turn;
+        if self.board.apply(mv).is_none() {
+            return false;
+        }
+        self.end_turn(turn);
+        true
+    }
+
+    /// Plays one step the way engines send them; the move is recorded once the turn passes.
+    pub fn play_step(&mut self, mv: PieceMove) -> bool {
+        let turn = self.board.turn;
+        if self.board.make_move(mv).is_none() {
+            return false;
+        }
+        self.end_turn(turn);
+        true
+    }
+
+    fn end_turn(&mut self, turn: Color) {
+        if self.board.turn == turn {
+            return;
+        }
+        let start = self.record.board();
+        let Some(played) = start.played_move(&self.board) else {
+            // The turn passed without a legal move, so the history no longer leads here; start it over.
+            self.record = GameRecord::new(self.board.clone());
+            self.progress_ply = 0;
+            return;
+        };
+        let man = start[played.from].piece.is_some_and(|piece| !piece.king);
+        self.record.play(&played);
+        if man || played.is_capture() {
+            self.progress_ply = self.record.ply();
+        }
+    }
+
+    pub fn outcome(&self) -> Option<Outcome> {
+        if self.board.legal_moves().is_empty() {
+            return Some(Outcome::Win(-self.board.turn));
+        }
+        let ply = self.record.ply();
+        if (self.progress_ply..=ply).filter(|&p| self.record.hash_at(p) == Some(self.board.hash)).count() >= 3 {
+            return Some(Outcome::Draw(DrawReason::Repetition));
+        }
+        if ply - self.progress_ply >= NO_PROGRESS_PLIES {
+            return Some(Outcome::Draw(DrawReason::NoProgress));
+        }
+        None
+    }
+}
@@ -1,29 +1,124 @@
-use std::{ops::{Neg, Index, IndexMut}, fmt::Display, io::Stdin};
+use std::{ops::{Neg, Index}, fmt::Display, io::BufRead, str::FromStr};
+
+pub mod ai;
+pub mod book;
+pub mod game;
+pub mod pdn;
+pub mod perft;
+pub mod record;
+pub mod rules;
+pub mod tablebase;
+pub mod tt;
+#[cfg(feature = "serde")]
+mod serde_impls;
+pub use ai::AI;
+pub use book::{OpeningBook, BookEntry};
+pub use game::{Game, Outcome, DrawReason};
+pub use pdn::{PdnGame, PdnResult};
+pub use record::GameRecord;
+pub use rules::{RuleSet, CapturePriority, Promotion};
+pub use tablebase::{Tablebase, TablebaseValue, Material, GenerateError};
+pub use tt::{TranspositionTable, TableEntry, TableStats, Bound};
 
 pub const DIRS: [MoveDir; 4] = [MoveDir::TopLeft, MoveDir::TopRight, MoveDir::DownLeft, MoveDir::DownRight];
 
 pub const LOST: i64 = -1_000_000;
 pub const WIN: i64 = 1_000_000;
 
-const BOARD_HASH: [[u64; 5]; 32] = [[16274875884252984956, 6074242489566749766, 9833923972892947155, 7089371412906992495, 4193949107282483348], [15192542410057855063, 2950379029391061177, 2373928829110185512, 8667796823930509732, 2006857113059836781], [17240502614927115812, 12650176439911112037, 16125475776992225402, 11215163897880425331, 16455025669698267593], [11982543824720674500, 11689768940945036012, 1258177404119886166, 8104243074131419772, 753131466122527109], [5318173331346675443, 14523429489948051288, 2903763423064359962, 10306871076669579102, 2103227662941222358], [18241584916984079752, 13944034434707060452, 8841817278909708425, 5182379259393751951, 2789829155049047510], [15586182909397208209, 7103835988119141559, 17086617251032100259, 16877032059503125526, 8766887688644728144], [6540727800511520548, 6087239248953928034, 112912877411321010, 15751349590952983607, 2608299175441640158], [6132894230358354261, 14596941937808897107, 9924100145400722741, 7649354081233957204, 3058598957732628883], [11343764717944437165, 15755518617544329124, 6064335707334156786, 5073459857266571696, 4764418664764831165], [17998075494648978202, 16724564891883453319, 11837820466397133977, 11222719033507765001, 7931666523861024232], [14688744633221763290, 2948439666112241851, 14895553529268023191, 11365739036837564630, 5910969438058883972], [10489398035197848729, 4251354873594885813, 9816160821033845909, 8877403550883625480, 2355787098486010388], [14976113501128997345, 5491960235926554482, 3559849850477081910, 8014340327482234447, 4322871156891124200], [3683988696001395439, 4882476044588243243, 16587843634165443074, 8109655804037719945, 18236702457620548315], [16478896971343697932, 2556331128272077451, 16115011888816126865, 11585170964533148637, 6407137973729341140], [15780380035285949964, 632153719320751506, 4255706508398326603, 18150830695095064412, 18283698207855421282], [12313990497803618457, 15452249877570647861, 13585575315835423120, 2786519215050216957, 8545240742438052079], [17024915352496284209, 15073728859364694043, 7077878969339855245, 13010434356424420399, 15962988367088501250], [15617254387641735532, 10551017970627490131, 7626411542069179899, 13272911875645299556, 10360731977783737055], [16679974993836505367, 7662577849883777022, 12774452934694025000, 10914284036027555555, 10591249164533632114], [8453525630454445311, 2929771814103333481, 12940075245728365955, 9062661811511429198, 5842497726739344737], [12414747509308103701, 549284408586433720, 8911455846781031434, 2622552561828178426, 1210952810793058161], [3121852042559984420, 3048134778644314244, 8161665141900449520, 9147488648106066446, 12265647805177820080], [8679851546982509133, 7227058976955123511, 8307499256195324678, 17622045472825632107, 9022920146413765599], [5729706023617262200, 5517073501976604600, 7811466911279522574, 12905652231906695362, 489690509462280198], [8804852051782848241, 3944976850500162559, 17201187752614130920, 9841944095998850095, 5985447382767007574], [5056754302316352595, 2476016181107259500, 6605155721705668485, 7410297421247171055, 18225642677703825547], [12978029578113286232, 8114842689987532415, 989965311472109006, 9842879144816655502, 4140701197748075506], [9398629978726996922, 12394352806349394525, 10918074777531381091, 5913083564929344805, 15577509057550588626], [2246475788457821352, 9799080289414864288, 10045343415915639585, 4416598120643402505, 6013702739115514033], [7335658778501375509, 18006933981810827233, 6424480005319050942, 2564622997727966525, 721114313271069757]];
+const BOARD_HASH: [[u64; 5]; 50] = [[16274875884252984956, 6074242489566749766, 9833923972892947155, 7089371412906992495, 4193949107282483348], [15192542410057855063, 2950379029391061177, 2373928829110185512, 8667796823930509732, 2006857113059836781], [17240502614927115812, 12650176439911112037, 16125475776992225402, 11215163897880425331, 16455025669698267593], [11982543824720674500, 11689768940945036012, 1258177404119886166, 8104243074131419772, 753131466122527109], [5318173331346675443, 14523429489948051288, 2903763423064359962, 10306871076669579102, 2103227662941222358], [18241584916984079752, 13944034434707060452, 8841817278909708425, 5182379259393751951, 2789829155049047510], [15586182909397208209, 7103835988119141559, 17086617251032100259, 16877032059503125526, 8766887688644728144], [6540727800511520548, 6087239248953928034, 112912877411321010, 15751349590952983607, 2608299175441640158], [6132894230358354261, 14596941937808897107, 9924100145400722741, 7649354081233957204, 3058598957732628883], [11343764717944437165, 15755518617544329124, 6064335707334156786, 5073459857266571696, 4764418664764831165], [17998075494648978202, 16724564891883453319, 11837820466397133977, 11222719033507765001, 7931666523861024232], [14688744633221763290, 2948439666112241851, 14895553529268023191, 11365739036837564630, 5910969438058883972], [10489398035197848729, 4251354873594885813, 9816160821033845909, 8877403550883625480, 2355787098486010388], [14976113501128997345, 5491960235926554482, 3559849850477081910, 8014340327482234447, 4322871156891124200], [3683988696001395439, 4882476044588243243, 16587843634165443074, 8109655804037719945, 18236702457620548315], [16478896971343697932, 2556331128272077451, 16115011888816126865, 11585170964533148637, 6407137973729341140], [15780380035285949964, 632153719320751506, 4255706508398326603, 18150830695095064412, 18283698207855421282], [12313990497803618457, 15452249877570647861, 13585575315835423120, 2786519215050216957, 8545240742438052079], [17024915352496284209, 15073728859364694043, 7077878969339855245, 13010434356424420399, 15962988367088501250], [15617254387641735532, 10551017970627490131, 7626411542069179899, 13272911875645299556, 10360731977783737055], [16679974993836505367, 7662577849883777022, 12774452934694025000, 10914284036027555555, 10591249164533632114], [8453525630454445311, 2929771814103333481, 12940075245728365955, 9062661811511429198, 5842497726739344737], [12414747509308103701, 549284408586433720, 8911455846781031434, 2622552561828178426, 1210952810793058161], [3121852042559984420, 3048134778644314244, 8161665141900449520, 9147488648106066446, 12265647805177820080], [8679851546982509133, 7227058976955123511, 8307499256195324678, 17622045472825632107, 9022920146413765599], [5729706023617262200, 5517073501976604600, 7811466911279522574, 12905652231906695362, 489690509462280198], [8804852051782848241, 3944976850500162559, 17201187752614130920, 9841944095998850095, 5985447382767007574], [5056754302316352595, 2476016181107259500, 6605155721705668485, 7410297421247171055, 18225642677703825547], [12978029578113286232, 8114842689987532415, 989965311472109006, 9842879144816655502, 4140701197748075506], [9398629978726996922, 12394352806349394525, 10918074777531381091, 5913083564929344805, 15577509057550588626], [2246475788457821352, 9799080289414864288, 10045343415915639585, 4416598120643402505, 6013702739115514033], [7335658778501375509, 18006933981810827233, 6424480005319050942, 2564622997727966525, 721114313271069757], [11141482602811556812, 15175871059779327898, 9856530228291709319, 7576338146045658142, 7603397523850062284], [2629094940244842092, 16127105691546479104, 16208308826990264365, 1971520006991534769, 13475377470064449906], [11871970042822802816, 6569832121322564539, 4960866838103104178, 12809809386643345829, 7230122537687550170], [11098632675341183370, 13429509662907199224, 9566601201685634535, 265130637907597385, 9562427761173001673], [13717793411679296131, 13771585960361014555, 18318501704816613681, 2654987150519745104, 18355514130084780075], [3189439986317156294, 18145312089070091840, 15391960398970981209, 16431220890692937327, 469230686389210034], [18192438442457538202, 1975833057083517100, 14111807943909501417, 17672118783185777910, 11620062258128133391], [12944802052803867289, 1164301600398763518, 946759842613437811, 14037259238363115229, 10727099630759332724], [5197579811924179556, 3609546134822094946, 16288146580336795305, 4207038750101649390, 5289703775036644398], [5478025974386176882, 15318654959956051919, 14174032286897727094, 6273041122436445229, 5921409241945354422], [12050017411472045244, 12534474329880650594, 8782592052275225596, 7022532662825156837, 6879026698997931913], [1388083703706626025, 4045698890155640601, 12156559378575610601, 8144569993413894788, 5109969775461378473], [2005723433549572715, 7078215445283927888, 9617549363264089003, 17971554029349313426, 6165921758995350146], [14885085354691977343, 3828164539871121263, 4607991474227467764, 1841792443299305476, 16832592499151521859], [15982761543267900482, 16330569201596425737, 6485914174406074121, 5602698667857773182, 18312238779306911387], [9273007724519405624, 3387669847155440320, 13940664367611227527, 11424535135683773822, 9689176096226831227], [10410367731255802177, 14340774110474198508, 13039495838580932251, 4369572786833663480, 643855588207477684], [14911528503104305755, 4733354286694542127, 7386664562891333554, 14842395011072042063, 15709589994406518417]];
+/// Marks a piece jumped earlier in the move in progress, which is still on its square.
+const CAPTURED_HASH: [u64; 50] = [11316057959865575759, 17623331038584812440, 15657428676203985001, 6188306475286465549, 5970674009952839672, 17680348015294599942, 1798696929071874617, 6271570113043309864, 2803242637351691297, 18029502953130356359, 9377829017422336235, 10935747501905501392, 10824409421167589091, 10492067628235292057, 3940135183513311601, 4738689128649210185, 5873285644435127587, 13409051996444939884, 10197766627520727650, 10877354385188601816, 14101230968290834581, 5313523421523442992, 2605207807217928310, 10930627472991623448, 1407284141885381399, 3170418367074308270, 17295640960166821241, 7308518159912831087, 6216439191785565869, 1524546590814950941, 9092152340715058256, 3616181548261190096, 8172296880850415307, 15345610573386741113, 3430390704154440293, 13403917744120100222, 17053287103748106695, 15653185237735557518, 2580374781574015014, 11850357965340499626, 5547835237775907461, 132756096214939501, 3372739353029353217, 15360644020570016632, 16674361955563951656, 17730693488288255956, 13537809688248772888, 11274007308538546650, 17831360058729962116, 5805498773211241738];
 const WHITE_TURN_HASH: u64 = 8563708190896211681;
 const BLACK_TURN_HASH: u64 = 1765425214959844302;
 
-#[derive(Clone, PartialEq, Eq, Copy, Default)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The input ended before `expected` was read.
+    MissingInput { expected: &'static str },
+    /// `found` is not a valid `expected`.
+    InvalidValue { expected: &'static str, found: String },
+    /// The board grid is malformed at `line` (1-based, header included) and, if known, at cell `column` (1-based).
+    InvalidGrid { line: usize, column: Option<usize>, reason: &'static str },
+    Io(std::io::ErrorKind)
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::MissingInput { expected } => write!(f, "missing {}", expected),
+            ParseError::InvalidValue { expected, found } => write!(f, "invalid {}: {:?}", expected, found),
+            ParseError::InvalidGrid { line, column: Some(column), reason } => write!(f, "invalid board at line {}, cell {}: {}", line, column, reason),
+            ParseError::InvalidGrid { line, column: None, reason } => write!(f, "invalid board at line {}: {}", line, reason),
+            ParseError::Io(kind) => write!(f, "read failed: {}", kind)
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<std::io::Error> for ParseError {
+    fn from(err: std::io::Error) -> Self {
+        ParseError::Io(err.kind())
+    }
+}
+
+/// Joins the errors of `Board::validate` for a one-line message.
+pub fn describe_errors(errors: &[PositionError]) -> String {
+    errors.iter().map(|err| err.to_string()).collect::<Vec<_>>().join("; ")
+}
+
+/// A problem `Board::validate` found in a position that parsed fine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PositionError {
+    /// `color` has more pieces than it starts with.
+    TooManyPieces { color: Color, count: usize },
+    /// A man on the row where it should have been crowned, outside a capture in progress.
+    UncrownedOnPromotionRow(CellPos),
+    /// `must_jump` lists a square without a piece of the side to move.
+    MustJumpWithoutPiece(CellPos),
+    /// `must_jump` lists a piece that has nothing to capture.
+    MustJumpWithoutCapture(CellPos),
+    /// `must_jump` is neither a single piece continuing a capture nor the pieces the rules force to capture.
+    ForcedJumpsMismatch { expected: Vec<CellPos> }
+}
+
+impl Display for PositionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PositionError::TooManyPieces { color, count } => write!(f, "{} has {} pieces", color, count),
+            PositionError::UncrownedOnPromotionRow(cp) => write!(f, "uncrowned man on {}", cp),
+            PositionError::MustJumpWithoutPiece(cp) => write!(f, "must jump from {} without a piece to move", cp),
+            PositionError::MustJumpWithoutCapture(cp) => write!(f, "must jump from {} without a capture", cp),
+            PositionError::ForcedJumpsMismatch { expected } => {
+                let expected = expected.iter().map(|cp| cp.to_string()).collect::<Vec<_>>();
+                write!(f, "must jump should be [{}]", expected.join(" "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for PositionError {}
+
+impl ParseError {
+    fn invalid(expected: &'static str, found: &str) -> Self {
+        ParseError::InvalidValue { expected, found: found.to_string() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Copy, Default)]
 pub enum Color {
     White,
     #[default]
     Black
 }
 
-impl Color {
-    pub fn from_str(s: &str) -> Self {
-        if s=="white" {
-            Self::White
-        }
-        else {
-            assert!(s=="black");
-            Self::Black
+impl FromStr for Color {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "white" => Ok(Self::White),
+            "black" => Ok(Self::Black),
+            _ => Err(ParseError::invalid("color", s))
         }
     }
 }
@@ -58,7 +153,7 @@ pub struct Cell {
     pub piece: Option<Piece>
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct CellPos {
     pub col: usize,
     pub row: usize
@@ -71,21 +166,43 @@ impl Display for CellPos {
     }
 }
 
+impl FromStr for CellPos {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let &[l, n] = s.as_bytes() else {
+            return Err(ParseError::invalid("square", s));
+        };
+        if !(b'A'..=b'J').contains(&l) || !n.is_ascii_digit() {
+            return Err(ParseError::invalid("square", s));
+        }
+        Ok(Self {
+            col: (l - b'A') as usize,
+            row: (n - b'0') as usize
+        })
+    }
+}
+
 impl CellPos {
     pub fn new(row: usize, col: usize) -> Self {
         Self {col, row}
     }
-    pub fn from_str(s: &str) -> Self {
-        let mut ch = s.chars();
 
-        let l = ch.next().unwrap() as u8 - 'A' as u8;
-        let n = ch.next().unwrap() as u8 - '0' as u8; 
-        Self {
-            col: l as usize,
-            row: n as usize
-        } 
+    /// The square with standard number `n` (1-32, 1 at the top left, next to Black's side) on an 8x8 board.
+    pub fn from_number(n: usize) -> Option<Self> {
+        GEOMETRY_8.numbered(n)
+    }
+
+    /// Standard 1-32 number of the square, `None` for light squares.
+    pub fn number(self) -> Option<usize> {
+        GEOMETRY_8.number(self)
     }
     pub fn shift(self, mv_dir: MoveDir) -> Option<CellPos> {
+        self.shift_on(mv_dir, 8)
+    }
+
+    /// Like `shift`, on a `size`x`size` board.
+    pub fn shift_on(self, mv_dir: MoveDir, size: usize) -> Option<CellPos> {
+        let last = size-1;
         match mv_dir {
             MoveDir::DownLeft => {
                 if self.col == 0 || self.row == 0 {
@@ -94,19 +211,19 @@ impl CellPos {
                 Some(Self {col: self.col-1, row: self.row-1})
             }
             MoveDir::DownRight => {
-                if self.row == 0 || self.col == 7 {
+                if self.row == 0 || self.col >= last {
                     return None;
                 }
                 Some(Self {col: self.col+1, row: self.row-1})
             }
             MoveDir::TopLeft => {
-                if self.row == 7 || self.col == 0 {
+                if self.row >= last || self.col == 0 {
                     return None;
                 }
                 Some(Self {col: self.col-1, row: self.row+1})
             }
             MoveDir::TopRight => {
-                if self.row == 7 || self.col == 7 {
+                if self.row >= last || self.col >= last {
                     return None;
                 }
                 Some(Self {col: self.col+1, row: self.row+1})
@@ -115,6 +232,36 @@ impl CellPos {
     }
 }
 
+/// How squares and moves are written in the engine protocol.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum Notation {
+    /// `C2`; a step is `C2 tr`, a whole move `C2xE4xG6`.
+    #[default]
+    Algebraic,
+    /// Standard square numbers; moves are always whole, `9-14` or `22x15x8`.
+    Numeric
+}
+
+impl FromStr for Notation {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "algebraic" => Ok(Self::Algebraic),
+            "numeric" => Ok(Self::Numeric),
+            _ => Err(ParseError::invalid("notation", s))
+        }
+    }
+}
+
+impl Display for Notation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Notation::Algebraic => write!(f, "algebraic"),
+            Notation::Numeric => write!(f, "numeric")
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum MoveDir {
     TopRight,
@@ -124,13 +271,32 @@ pub enum MoveDir {
 }
 
 impl MoveDir {
-    pub fn from_str(s: &str) -> Self {
+    pub fn opposite(self) -> Self {
+        match self {
+            MoveDir::TopRight => MoveDir::DownLeft,
+            MoveDir::TopLeft => MoveDir::DownRight,
+            MoveDir::DownRight => MoveDir::TopLeft,
+            MoveDir::DownLeft => MoveDir::TopRight
+        }
+    }
+    /// Whether an uncrowned piece of `color` may move in this direction.
+    pub fn is_forward(self, color: Color) -> bool {
+        match color {
+            Color::White => self == MoveDir::TopLeft || self == MoveDir::TopRight,
+            Color::Black => self == MoveDir::DownLeft || self == MoveDir::DownRight
+        }
+    }
+}
+
+impl FromStr for MoveDir {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "tr" => MoveDir::TopRight,
-            "tl" => MoveDir::TopLeft,
-            "dr" => MoveDir::DownRight,
-            "dl" => MoveDir::DownLeft,
-            _ => panic!("Invalid move direction format.")
+            "tr" => Ok(MoveDir::TopRight),
+            "tl" => Ok(MoveDir::TopLeft),
+            "dr" => Ok(MoveDir::DownRight),
+            "dl" => Ok(MoveDir::DownLeft),
+            _ => Err(ParseError::invalid("move direction", s))
         }
     }
 }
@@ -158,47 +324,279 @@ impl Display for PieceMove {
     }
 }
 
+impl FromStr for PieceMove {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut sp = s.split_whitespace();
+        let pos = sp.next().ok_or(ParseError::MissingInput { expected: "square" })?.parse()?;
+        let dir = sp.next().ok_or(ParseError::MissingInput { expected: "move direction" })?.parse()?;
+        if let Some(extra) = sp.next() {
+            return Err(ParseError::invalid("end of move", extra));
+        }
+
+        Ok(Self {
+            pos, dir
+        })
+    }
+}
+
 impl PieceMove {
-    pub fn from_stdin(stdin: &mut Stdin) -> Self {
+    /// Splits a move in 1-32 numeric notation (`9-14`, `22x15x8`) into the steps `Board::make_move` plays.
+    pub fn parse_numeric(s: &str) -> Result<Vec<Self>, ParseError> {
+        let squares = s.split(['-', 'x']).map(|n| {
+            n.parse().ok().and_then(CellPos::from_number).ok_or_else(|| ParseError::invalid("square number", n))
+        }).collect::<Result<Vec<_>, _>>()?;
+        if squares.len() < 2 {
+            return Err(ParseError::invalid("move", s));
+        }
+        squares.windows(2).map(|pair| {
+            let (from, to) = (pair[0], pair[1]);
+            if from.row == to.row || from.row.abs_diff(to.row) != from.col.abs_diff(to.col) {
+                return Err(ParseError::invalid("diagonal move", s));
+            }
+            Ok(PieceMove {pos: from, dir: direction(from, to)})
+        }).collect()
+    }
+
+    pub fn read_from<R: BufRead>(reader: &mut R) -> Result<Self, ParseError> {
         let mut content = String::new();
-        stdin.read_line(&mut content).unwrap();
-        content = content.trim().to_string();
-        
-        Self::from_str(&content)
+        if reader.read_line(&mut content)? == 0 {
+            return Err(ParseError::MissingInput { expected: "move" });
+        }
+        content.parse()
+    }
+}
+
+/// A complete turn: the start square, every square the piece lands on and the pieces it captures.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Move {
+    pub from: CellPos,
+    pub path: Vec<CellPos>,
+    pub captures: Vec<CellPos>
+}
+
+impl Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sep = if self.is_capture() { "x" } else { "-" };
+        write!(f, "{}", self.from)?;
+        for cp in &self.path {
+            write!(f, "{}{}", sep, cp)?;
+        }
+        Ok(())
+    }
+}
+
+impl Move {
+    pub fn is_capture(&self) -> bool {
+        !self.captures.is_empty()
     }
-    pub fn from_str(s: &str) -> Self {
-        let mut sp = s.split_whitespace();
-        let pos = CellPos::from_str(sp.next().unwrap());
-        let dir = MoveDir::from_str(sp.next().unwrap());
 
+    pub fn to(&self) -> CellPos {
+        *self.path.last().unwrap()
+    }
+
+    /// The steps `Board::make_move` plays one after another to perform this move. A step cannot say how far
+    /// a flying king goes, so under those rules the move is replayed along `path` with `Board::make_move_to`.
+    pub fn steps(&self) -> Vec<PieceMove> {
+        let mut pos = self.from;
+        self.path.iter().map(|&to| {
+            let step = PieceMove {pos, dir: direction(pos, to)};
+            pos = to;
+            step
+        }).collect()
+    }
+}
+
+static CELLS: [Cell; 5] = [
+    Cell {piece: None},
+    Cell {piece: Some(Piece {king: false, color: Color::White})},
+    Cell {piece: Some(Piece {king: false, color: Color::Black})},
+    Cell {piece: Some(Piece {king: true, color: Color::White})},
+    Cell {piece: Some(Piece {king: true, color: Color::Black})},
+];
+
+/// Bitboard layout of a `size`x`size` board: dark square (row, col) is bit `row*half + col/2`.
+struct Geometry {
+    size: usize,
+    half: usize,
+    all: u64,
+    rows_even: u64,
+    /// Squares in the first column of their row (column 0 on even rows).
+    left_edge: u64,
+    /// Squares in the last column of their row (column `size-1` on odd rows).
+    right_edge: u64
+}
+
+static GEOMETRY_8: Geometry = Geometry::new(8);
+static GEOMETRY_10: Geometry = Geometry::new(10);
+
+impl Geometry {
+    const fn new(size: usize) -> Self {
+        let half = size/2;
+        let mut rows_even = 0;
+        let mut left_edge = 0;
+        let mut right_edge = 0;
+        let mut row = 0;
+        while row < size {
+            if row%2 == 0 {
+                rows_even |= ((1 << half) - 1) << (row*half);
+            }
+            left_edge |= 1 << (row*half);
+            right_edge |= 1 << (row*half + half-1);
+            row += 1;
+        }
         Self {
-            pos, dir
+            size,
+            half,
+            all: (1 << (size*half)) - 1,
+            rows_even,
+            left_edge,
+            right_edge
         }
     }
+
+    fn of(rules: RuleSet) -> &'static Self {
+        match rules {
+            RuleSet::International => &GEOMETRY_10,
+            RuleSet::Standard | RuleSet::American | RuleSet::Russian | RuleSet::Brazilian | RuleSet::Pool | RuleSet::Italian => &GEOMETRY_8
+        }
+    }
+
+    /// Index of a dark square, `None` for light squares and squares off the board.
+    fn square(&self, cp: CellPos) -> Option<usize> {
+        if cp.row >= self.size || cp.col >= self.size || (cp.row+cp.col)%2 == 1 {
+            return None;
+        }
+        Some(cp.row*self.half + cp.col/2)
+    }
+
+    fn square_pos(&self, sq: usize) -> CellPos {
+        let row = sq/self.half;
+        CellPos {row, col: (sq%self.half)*2 + row%2}
+    }
+
+    /// Standard square number (1 at the top left, counting along rows towards the bottom right).
+    fn number(&self, cp: CellPos) -> Option<usize> {
+        let sq = self.square(cp)?;
+        Some((self.size-1-cp.row)*self.half + sq%self.half + 1)
+    }
+
+    fn numbered(&self, n: usize) -> Option<CellPos> {
+        let idx = n.checked_sub(1).filter(|&idx| idx < self.size*self.half)?;
+        let row = self.size-1 - idx/self.half;
+        Some(CellPos {row, col: (idx%self.half)*2 + row%2})
+    }
+
+    fn squares(&'static self, bb: u64) -> impl Iterator<Item = CellPos> {
+        bits(bb).map(move |sq| self.square_pos(sq))
+    }
+
+    /// Moves every bit of `bb` one square in `dir`, dropping bits that would leave the board.
+    fn shift(&self, bb: u64, dir: MoveDir) -> u64 {
+        let h = self.half;
+        let even = bb & self.rows_even;
+        let odd = bb & !self.rows_even;
+        let res = match dir {
+            MoveDir::TopLeft => ((even & !self.left_edge) << (h-1)) | (odd << h),
+            MoveDir::TopRight => (even << h) | ((odd & !self.right_edge) << (h+1)),
+            MoveDir::DownLeft => ((even & !self.left_edge) >> (h+1)) | (odd >> h),
+            MoveDir::DownRight => (even >> h) | ((odd & !self.right_edge) >> (h-1)),
+        };
+        res & self.all
+    }
+
+    /// The square next to `sq` in `dir`, if it is on the board.
+    fn step(&self, sq: usize, dir: MoveDir) -> Option<usize> {
+        let next = self.shift(1 << sq, dir);
+        (next != 0).then(|| next.trailing_zeros() as usize)
+    }
+
+    /// The row where men of `color` are crowned.
+    fn promotion_row(&self, color: Color) -> u64 {
+        let row = (1 << self.half) - 1;
+        match color {
+            Color::White => row << (self.all.count_ones() as usize - self.half),
+            Color::Black => row
+        }
+    }
+
+    /// The rows each side starts on: all but the two middle rows.
+    fn start_rows(&self, color: Color) -> u64 {
+        let rows = (1u64 << ((self.size/2 - 1)*self.half)) - 1;
+        match color {
+            Color::White => rows,
+            Color::Black => rows << ((self.size/2 + 1)*self.half)
+        }
+    }
+
+    /// `bb` turned by 180 degrees; square `sq` becomes `n-1-sq`.
+    fn rotate(&self, bb: u64) -> u64 {
+        bb.reverse_bits() >> (64 - self.size*self.half)
+    }
 }
 
-#[derive(Default, Clone)]
+fn bits(mut bb: u64) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if bb == 0 {
+            return None;
+        }
+        let sq = bb.trailing_zeros() as usize;
+        bb &= bb-1;
+        Some(sq)
+    })
+}
+
+fn direction(from: CellPos, to: CellPos) -> MoveDir {
+    match (to.row > from.row, to.col > from.col) {
+        (true, true) => MoveDir::TopRight,
+        (true, false) => MoveDir::TopLeft,
+        (false, true) => MoveDir::DownRight,
+        (false, false) => MoveDir::DownLeft
+    }
+}
+
+/// Everything `Board::unmake_move` needs to take back a step played by `Board::make_move`.
+#[derive(Clone)]
+pub struct MoveUndo {
+    /// The forced pieces as a square mask, so that taking a step back reuses the board's `must_jump` buffer.
+    must_jump: u64,
+    turn: Color,
+    hash: u64,
+    white: u64,
+    black: u64,
+    kings: u64,
+    captured: u64
+}
+
+#[derive(Clone)]
 pub struct Board {
     pub must_jump: Vec<CellPos>,
-    pub data: [[Cell; 8]; 8],
     pub turn: Color,
-    pub hash: u64
+    pub hash: u64,
+    rules: RuleSet,
+    white: u64,
+    black: u64,
+    kings: u64,
+    /// Pieces jumped earlier in the move in progress, left on the board until it completes.
+    captured: u64
 }
 
 impl Display for Board {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut s = String::new();
+        let size = self.size();
         s.push_str("   ");
-        for i in 0..8 {
+        for i in 0..size {
             let letter = ('A' as u8 + i as u8) as char;
             s.push(letter);
             s.push(' ');
         }
         s.push('\n');
-        for (i, row) in self.data.iter().enumerate().rev() {
+        for i in (0..size).rev() {
             s.push_str(format!("{}: ", i).as_str());
-            for cell in row.iter() {
-                let ch = match cell.piece {
+            for j in 0..size {
+                let ch = match self[cell(i, j)].piece {
                     Some(Piece {king: false, color: Color::White}) => "w",
                     Some(Piece {king: false, color: Color::Black}) => "b",
                     Some(Piece {king: true, color: Color::White}) => "W",
@@ -215,181 +613,372 @@ impl Display for Board {
     }
 }
 
+impl Default for Board {
+    fn default() -> Self {
+        let mut res = Self {
+            must_jump: vec![],
+            turn: Color::default(),
+            hash: 0,
+            rules: RuleSet::default(),
+            white: 0,
+            black: 0,
+            kings: 0,
+            captured: 0
+        };
+        res.recompute_hash();
+        res
+    }
+}
+
+impl FromStr for Board {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_grid(s)
+    }
+}
+
 impl Index<CellPos> for Board {
     type Output = Cell;
     fn index(&self, index: CellPos) -> &Self::Output {
-        &self.data[index.row][index.col]
-    }    
-}
-
-impl IndexMut<CellPos> for Board {
-    fn index_mut(&mut self, index: CellPos) -> &mut Self::Output {
-        &mut self.data[index.row][index.col]
+        let Some(sq) = self.geo().square(index) else {
+            return &CELLS[0];
+        };
+        &CELLS[self.cell_kind(sq)]
     }
 }
 
 impl Board {
     pub fn new() -> Self {
-        let mut data: [[Cell; 8]; 8] = Default::default();
-        for i in 0..8 {
-            for j in 0..8 {
-                if (i+j)%2 == 1 {
-                    continue;
-                }
-                let piece = if i < 3 {
-                    Some(Piece {king: false, color: Color::White})
-                }
-                else if i > 4 {
-                    Some(Piece {king: false, color: Color::Black})
-                }
-                else {
-                    None
-                };
-                data[i][j].piece = piece;
-            }
-        }
+        Self::with_rules(RuleSet::Standard)
+    }
+
+    /// The starting position of `rules`.
+    pub fn with_rules(rules: RuleSet) -> Self {
+        let geo = Geometry::of(rules);
         let mut res = Self {
-            data,
+            white: geo.start_rows(Color::White),
+            black: geo.start_rows(Color::Black),
+            turn: rules.first_to_move(),
+            rules,
             ..Default::default()
         };
         res.recompute_hash();
         res
     }
 
-    pub fn exists_valid_move(&self) -> bool {
-        let mut board = self.clone();
-        for i in 0..8 {
-            for j in 0..8 {
-                for dir in DIRS {
-                    if board.make_move(PieceMove { pos: CellPos {col: j, row: i}, dir }) {
-                        return true;
-                    }
-                }
-            }
+    pub fn rules(&self) -> RuleSet {
+        self.rules
+    }
+
+    /// Switches to rules played on the same board size, leaving the board untouched for rules played on another size.
+    pub fn set_rules(&mut self, rules: RuleSet) -> Result<(), ParseError> {
+        if rules.size() != self.size() {
+            return Err(ParseError::invalid("rules for this board size", &rules.to_string()));
         }
-        return false;
+        self.rules = rules;
+        self.find_forced_jumps();
+        Ok(())
+    }
+
+    pub fn size(&self) -> usize {
+        self.rules.size()
+    }
+
+    fn geo(&self) -> &'static Geometry {
+        Geometry::of(self.rules)
     }
 
-    pub fn make_move(&mut self, mv: PieceMove) -> bool {
-        let Some(cpiece) = self[mv.pos].piece else {
-            return false;
+    /// Number of `cp` in the standard numbering (1-32, or 1-50 on 10x10), `None` for light squares.
+    pub fn square_number(&self, cp: CellPos) -> Option<usize> {
+        self.geo().number(cp)
+    }
+
+    pub fn numbered_square(&self, n: usize) -> Option<CellPos> {
+        self.geo().numbered(n)
+    }
+
+    /// Parses a square written in `notation`, numbered for this board's size.
+    pub fn parse_square(&self, s: &str, notation: Notation) -> Result<CellPos, ParseError> {
+        match notation {
+            Notation::Algebraic => s.parse(),
+            Notation::Numeric => s.parse().ok().and_then(|n| self.numbered_square(n)).ok_or_else(|| ParseError::invalid("square number", s))
+        }
+    }
+
+    /// Bitboard of the pieces of `color`, one bit per dark square (`row*size/2 + col/2`).
+    pub fn pieces(&self, color: Color) -> u64 {
+        match color {
+            Color::White => self.white,
+            Color::Black => self.black
+        }
+    }
+
+    pub fn kings(&self) -> u64 {
+        self.kings
+    }
+
+    pub fn empty(&self) -> u64 {
+        !(self.white | self.black) & self.geo().all
+    }
+
+    /// Places `piece` on `cp`, keeping `hash` up to date.
+    pub fn set(&mut self, cp: CellPos, piece: Option<Piece>) {
+        let Some(sq) = self.geo().square(cp) else {
+            assert!(piece.is_none(), "Pieces can only be placed on dark squares.");
+            return;
         };
-        if cpiece.color != self.turn {
-            return false;
+        let bit = 1 << sq;
+        self.hash ^= BOARD_HASH[sq][self.cell_kind(sq)];
+        self.white &= !bit;
+        self.black &= !bit;
+        self.kings &= !bit;
+        if let Some(piece) = piece {
+            match piece.color {
+                Color::White => self.white |= bit,
+                Color::Black => self.black |= bit
+            }
+            if piece.king {
+                self.kings |= bit;
+            }
+        }
+        self.hash ^= BOARD_HASH[sq][self.cell_kind(sq)];
+    }
+
+    fn set_captured(&mut self, captured: u64) {
+        for sq in bits(self.captured ^ captured) {
+            self.hash ^= CAPTURED_HASH[sq];
         }
+        self.captured = captured;
+    }
 
-        if self.must_jump.len() != 0 && !self.must_jump.contains(&mv.pos) {
-            return false;
+    fn pass_turn(&mut self) {
+        self.turn = -self.turn;
+        self.hash ^= WHITE_TURN_HASH ^ BLACK_TURN_HASH;
+    }
+
+    /// 0 for an empty square, otherwise 1 + (black) + 2*(king); matches the layout of `BOARD_HASH`.
+    fn cell_kind(&self, sq: usize) -> usize {
+        let bit = 1 << sq;
+        if (self.white | self.black) & bit == 0 {
+            return 0;
+        }
+        let mut res = 1;
+        if self.kings & bit != 0 {
+            res += 2;
         }
+        if self.black & bit != 0 {
+            res += 1;
+        }
+        res
+    }
 
-        if !cpiece.king {
-            if cpiece.color == Color::White {
-                if mv.dir == MoveDir::DownLeft || mv.dir == MoveDir::DownRight {
-                    return false;
-                }
+    pub fn exists_valid_move(&self) -> bool {
+        if !self.must_jump.is_empty() {
+            return self.must_jump.iter().any(|&cp| self.can_jump(cp));
+        }
+        self.jumpers() | self.movers() != 0
+    }
+
+    /// Pieces of the side to move that have a quiet (non-capturing) step available.
+    fn movers(&self) -> u64 {
+        let own = self.pieces(self.turn);
+        let empty = self.empty();
+        let mut res = 0;
+        for dir in DIRS {
+            let pieces = if dir.is_forward(self.turn) { own } else { own & self.kings };
+            res |= self.geo().shift(empty, dir.opposite()) & pieces;
+        }
+        res
+    }
+
+    /// Pieces of the side to move that can capture, before any capture priority is applied.
+    fn jumpers(&self) -> u64 {
+        self.jumpers_of(self.turn)
+    }
+
+    /// Pieces of `color` that could capture if it were to move; pieces awaiting removal neither capture nor are captured.
+    fn jumpers_of(&self, color: Color) -> u64 {
+        let own = self.pieces(color) & !self.captured;
+        let opp = self.pieces(-color) & !self.captured;
+        let men_targets = if self.rules.men_capture_kings() { opp } else { opp & !self.kings };
+        let empty = self.empty();
+        let mut res = 0;
+        for dir in DIRS {
+            let back = dir.opposite();
+            let landing = self.geo().shift(empty, back);
+            if dir.is_forward(color) || self.rules.men_capture_backwards() {
+                res |= self.geo().shift(landing & men_targets, back) & own & !self.kings;
             }
-            else {
-                if mv.dir == MoveDir::TopLeft || mv.dir == MoveDir::TopRight {
-                    return false;
+            if !self.rules.flying_kings() {
+                res |= self.geo().shift(landing & opp, back) & own & self.kings;
+            }
+        }
+        if self.rules.flying_kings() {
+            for sq in bits(own & self.kings) {
+                if self.flying_king_can_capture(sq, opp) {
+                    res |= 1 << sq;
                 }
             }
         }
+        res
+    }
 
-        let Some(npos) = mv.pos.shift(mv.dir) else {
-            return false;
-        };
-
-        if let Some(npiece) = self[npos].piece {
-            if npiece.color == self.turn {
-                return false;
+    fn flying_king_can_capture(&self, sq: usize, opp: u64) -> bool {
+        let empty = self.empty();
+        DIRS.into_iter().any(|dir| {
+            let mut next = self.geo().step(sq, dir);
+            while let Some(s) = next {
+                if empty & (1 << s) == 0 {
+                    break;
+                }
+                next = self.geo().step(s, dir);
             }
-            let Some(nnpos) = npos.shift(mv.dir) else {
+            let Some(target) = next else {
                 return false;
             };
-            if self[nnpos].piece.is_some() {
-                return false;
-            }
-            self[nnpos].piece = Some(cpiece);
-            self[mv.pos].piece = None;
-            self[npos].piece = None;
-            if self.can_jump(nnpos) {
-                self.must_jump = vec![nnpos];
-            }
-            else {
-                self.turn = -self.turn;
-                self.find_forced_jumps();
-                self.recompute_hash();
+            opp & (1 << target) != 0 && self.geo().step(target, dir).is_some_and(|to| empty & (1 << to) != 0)
+        })
+    }
+
+    /// Plays one step of a move; returns `None` and leaves the board untouched if the step is illegal.
+    ///
+    /// A step goes to the nearest legal square in `mv.dir`. If the piece has to keep capturing afterwards
+    /// it stays in `must_jump` and the turn does not pass; `make_move_to` takes flying kings further.
+    pub fn make_move(&mut self, mv: PieceMove) -> Option<MoveUndo> {
+        if self.must_jump.is_empty() {
+            let to = mv.pos.shift_on(mv.dir, self.size())?;
+            return self.make_quiet_step(mv.pos, to);
+        }
+        let captures = self.piece_captures(mv.pos);
+        let landing = captures.iter().map(|m| m.path[0]).filter(|&to| direction(mv.pos, to) == mv.dir).min_by_key(|to| to.row.abs_diff(mv.pos.row))?;
+        self.make_capture_step(&captures, landing)
+    }
+
+    /// Plays one step of a move from `from` to `to`, which may be any distance away for a flying king.
+    pub fn make_move_to(&mut self, from: CellPos, to: CellPos) -> Option<MoveUndo> {
+        if self.must_jump.is_empty() {
+            return self.make_quiet_step(from, to);
+        }
+        let captures = self.piece_captures(from);
+        self.make_capture_step(&captures, to)
+    }
+
+    /// The capture sequences of the piece on `from`, or none if it is not one of the pieces that have to capture.
+    fn piece_captures(&self, from: CellPos) -> Vec<Move> {
+        if !self.must_jump.contains(&from) {
+            return vec![];
+        }
+        self.capture_moves(&[from])
+    }
+
+    fn make_quiet_step(&mut self, from: CellPos, to: CellPos) -> Option<MoveUndo> {
+        let mut sq = self.geo().square(from)?;
+        let piece = self[from].piece.filter(|piece| piece.color == self.turn)?;
+        let distance = from.row.abs_diff(to.row);
+        if distance == 0 || distance != from.col.abs_diff(to.col) {
+            return None;
+        }
+        let dir = direction(from, to);
+        if !piece.king && !dir.is_forward(self.turn) || distance > 1 && !(piece.king && self.rules.flying_kings()) {
+            return None;
+        }
+        for _ in 0..distance {
+            sq = self.geo().step(sq, dir)?;
+            if self.empty() & (1 << sq) == 0 {
+                return None;
             }
         }
+        Some(self.apply_unchecked(&Move {from, path: vec![to], captures: vec![]}))
+    }
+
+    /// Plays the first jump of the one of `captures` that lands on `landing`.
+    fn make_capture_step(&mut self, captures: &[Move], landing: CellPos) -> Option<MoveUndo> {
+        let first = captures.iter().find(|m| m.path[0] == landing)?;
+        if first.path.len() == 1 {
+            return Some(self.apply_unchecked(first));
+        }
+
+        let (mut piece, jumped, to) = (self[first.from].piece?, self.geo().square(first.captures[0])?, self.geo().square(landing)?);
+        let undo = self.undo_record();
+        self.set(first.from, None);
+        if self.rules.removes_captures_at_end() {
+            self.set_captured(self.captured | 1 << jumped);
+        }
         else {
-            if self.must_jump.len() != 0 {
-                return false;
-            }
-            self[mv.pos].piece = None;
-            self[npos].piece = Some(cpiece);
-            self.turn = -self.turn;
-            self.find_forced_jumps();
-            self.recompute_hash();
+            self.set(first.captures[0], None);
         }
-        self.promote_pawns();
+        if self.rules.promotion() == Promotion::Immediate && self.geo().promotion_row(self.turn) & (1 << to) != 0 {
+            piece.king = true;
+        }
+        self.set(landing, Some(piece));
+        self.must_jump.clear();
+        self.must_jump.push(landing);
+
+        debug_assert_eq!(self.hash, self.full_hash(), "Incremental hash diverged from full recomputation.");
+
+        Some(undo)
+    }
+
+    /// Restores the position from before the `make_move` call that returned `undo`.
+    pub fn unmake_move(&mut self, undo: MoveUndo) {
+        let geo = self.geo();
+        self.must_jump.clear();
+        self.must_jump.extend(geo.squares(undo.must_jump));
+        self.turn = undo.turn;
+        self.hash = undo.hash;
+        self.white = undo.white;
+        self.black = undo.black;
+        self.kings = undo.kings;
+        self.captured = undo.captured;
+    }
 
-        true
+    fn undo_record(&self) -> MoveUndo {
+        MoveUndo {
+            must_jump: self.must_jump.iter().fold(0, |mask, &cp| mask | 1 << self.geo().square(cp).unwrap()),
+            turn: self.turn,
+            hash: self.hash,
+            white: self.white,
+            black: self.black,
+            kings: self.kings,
+            captured: self.captured
+        }
     }
 
+    /// Refills `must_jump` in place, so playing a move does not allocate a new list.
     fn find_forced_jumps(&mut self) {
-        self.must_jump = vec![];
-        let mut k = false;
-        for (i, row) in self.data.iter().enumerate() {
-            for (j, cell) in row.iter().enumerate() {
-                let Some(piece) = cell.piece else {
-                    continue;
-                };
-                let cp = CellPos {row: i, col: j};
-                if piece.color == self.turn && self.can_jump(cp) {
-                    if piece.king && !k  {
-                        self.must_jump = vec![];
-                        k = true;
-                    }
-                    if piece.king || !k {
-                        self.must_jump.push(cp);
-                    }
-                }
+        let jumpers = self.jumpers();
+        let forced = match self.rules.capture_priority() {
+            CapturePriority::KingsFirst if jumpers & self.kings != 0 => jumpers & self.kings,
+            CapturePriority::Maximum | CapturePriority::Italian if jumpers != 0 => {
+                self.capture_moves(&self.geo().squares(jumpers).collect::<Vec<_>>()).iter()
+                    .fold(0, |mask, mv| mask | 1 << self.geo().square(mv.from).unwrap())
             }
-        }
+            _ => jumpers
+        };
+        let geo = self.geo();
+        self.must_jump.clear();
+        self.must_jump.extend(geo.squares(forced));
     }
 
+    /// Has to be called after `turn` is changed directly; moves keep `hash` updated incrementally.
     pub fn recompute_hash(&mut self) {
-        self.hash = 0;
-        if self.turn == Color::White {
-            self.hash = WHITE_TURN_HASH;
+        self.hash = self.full_hash();
+    }
+
+    fn full_hash(&self) -> u64 {
+        let mut hash = if self.turn == Color::White {
+            WHITE_TURN_HASH
         }
         else {
-            self.hash = BLACK_TURN_HASH;
+            BLACK_TURN_HASH
+        };
+        for (sq, sq_hash) in BOARD_HASH.iter().enumerate().take(self.geo().all.count_ones() as usize) {
+            hash ^= sq_hash[self.cell_kind(sq)];
         }
-        for i in 0..8 {
-            for j in 0..8 {
-                if (i+j)%2 == 1 {
-                    continue;
-                }
-                let cp = cell(i, j);
-                let pid = i*4+j/2;
-                let cell_hash = if let Some(piece) = self[cp].piece {
-                    let mut res = 1;
-                    if piece.king {
-                        res += 2;
-                    }
-                    if piece.color == Color::Black {
-                        res += 1;
-                    }
-                    BOARD_HASH[pid][res]
-                }
-                else {
-                    BOARD_HASH[pid][0]
-                };
-
-                self.hash ^= cell_hash;
-            }
+        for sq in bits(self.captured) {
+            hash ^= CAPTURED_HASH[sq];
         }
+        hash
     }
 
     pub fn can_jump(&self, cp: CellPos) -> bool {
@@ -399,112 +988,432 @@ impl Board {
 
         assert!(piece.color == self.turn);
 
-        let mut dirs_to_check = vec![]; 
-        if piece.king || piece.color == Color::White {
-            dirs_to_check.push(MoveDir::TopRight);
-            dirs_to_check.push(MoveDir::TopLeft);
-        }
-        if piece.king || piece.color == Color::Black {
-            dirs_to_check.push(MoveDir::DownLeft);
-            dirs_to_check.push(MoveDir::DownRight);
-        }
+        self.jumpers() & (1 << self.geo().square(cp).unwrap()) != 0
+    }
 
-        for dir in dirs_to_check {
-            let Some(np) = cp.shift(dir) else {
-                continue;
-            };
-            let Some(npiece) = self[np].piece else {
-                continue;
-            };
-            if npiece.color != -self.turn {
-                continue;
-            }
-            let Some(nnp) = np.shift(dir) else {
-                continue;
-            };
+    /// Reads the grid printed by `Display for Board`: a header, then one line per row from the top.
+    pub fn read_from<R: BufRead>(reader: &mut R) -> Result<Self, ParseError> {
+        Self::parse_grid(&Self::read_grid(reader)?)
+    }
+
+    /// Reads a grid like `read_from`, which must have the size `rules` are played on.
+    pub fn read_with_rules<R: BufRead>(reader: &mut R, rules: RuleSet) -> Result<Self, ParseError> {
+        Self::parse_grid_with_rules(&Self::read_grid(reader)?, rules)
+    }
 
-            if self[nnp].piece.is_none() {
-                return true;
+    fn read_grid<R: BufRead>(reader: &mut R) -> Result<String, ParseError> {
+        let mut content = String::new();
+        if reader.read_line(&mut content)? == 0 {
+            return Err(ParseError::InvalidGrid { line: 1, column: None, reason: "unexpected end of input" });
+        }
+        let size = content.split_whitespace().count();
+        for line in 1..=size {
+            if reader.read_line(&mut content)? == 0 {
+                return Err(ParseError::InvalidGrid { line: line+1, column: None, reason: "unexpected end of input" });
             }
         }
+        Ok(content)
+    }
 
-        return false;
-        
+    /// Parses the grid printed by `Display` under the first of `RuleSet::ALL` played on its size,
+    /// so an 8x8 grid gets standard rules and a 10x10 one international rules.
+    pub fn parse_grid(s: &str) -> Result<Self, ParseError> {
+        let columns = s.lines().next().map_or(0, |header| header.split_whitespace().count());
+        let Some(rules) = RuleSet::ALL.into_iter().find(|rules| rules.size() == columns) else {
+            return Err(ParseError::InvalidGrid { line: 1, column: None, reason: "expected 8 or 10 columns" });
+        };
+        Self::parse_grid_with_rules(s, rules)
     }
 
-    pub fn from_stdin(stdin: &Stdin) -> Self {
-        let mut data: [[Cell; 8]; 8] = Default::default();
-        let content = (0..9).into_iter().map(|_| {
-            let mut s = String::new();
-            stdin.read_line(&mut s).unwrap();
-            s.trim().split_whitespace().skip(1).map(|s| s.to_string()).collect::<Vec<_>>()
-        }).skip(1).collect::<Vec<_>>();
-        assert!(content.len() == 8);
-        for (row, row_data) in content.into_iter().enumerate() {
-            // eprintln!("{:?}", row_data);
-            assert!(row_data.len() == 8);
+    /// Parses the grid printed by `Display`, which must have the size `rules` are played on.
+    pub fn parse_grid_with_rules(s: &str, rules: RuleSet) -> Result<Self, ParseError> {
+        let mut lines = s.lines();
+        let Some(header) = lines.next() else {
+            return Err(ParseError::InvalidGrid { line: 1, column: None, reason: "missing header" });
+        };
+        if header.split_whitespace().count() != rules.size() {
+            return Err(ParseError::InvalidGrid { line: 1, column: None, reason: "wrong number of columns for the rules" });
+        }
+        let mut result = Self { rules, ..Self::default() };
+        result.recompute_hash();
+        let size = result.size();
+        for row in 0..size {
+            let line = row+2;
+            let Some(row_data) = lines.next() else {
+                return Err(ParseError::InvalidGrid { line, column: None, reason: "missing row" });
+            };
+            let row_data = row_data.split_whitespace().skip(1).collect::<Vec<_>>();
+            if row_data.len() != size {
+                return Err(ParseError::InvalidGrid { line, column: None, reason: "wrong number of cells" });
+            }
             for (col, ch) in row_data.into_iter().enumerate() {
-                assert!(ch.len() == 1);
-                let piece = match ch.as_str() {
+                let column = Some(col+1);
+                let piece = match ch {
                     "w" => Some(Piece {king: false, color: Color::White}),
                     "b" => Some(Piece {king: false, color: Color::Black}),
                     "W" => Some(Piece {king: true, color: Color::White}),
                     "B" => Some(Piece {king: true, color: Color::Black}),
-                    _ => { assert!(ch == "."); None }
+                    "." => None,
+                    _ => return Err(ParseError::InvalidGrid { line, column, reason: "expected one of w, b, W, B, ." })
                 };
-                data[8-row-1][col].piece = piece;
+                let cp = cell(size-row-1, col);
+                if piece.is_some() && result.geo().square(cp).is_none() {
+                    return Err(ParseError::InvalidGrid { line, column, reason: "piece on a light square" });
+                }
+                result.set(cp, piece);
             }
         }
 
-        let mut result = Self {
-            data,
-            ..Default::default()
+        result.find_forced_jumps();
+        Ok(result)
+    }
+
+    /// Parses a PDN FEN string such as `W:W21,22,K30:B1-3` (side to move, then each side's square numbers, `K` marking kings).
+    pub fn from_fen(s: &str, rules: RuleSet) -> Result<Self, ParseError> {
+        let invalid = || ParseError::invalid("FEN", s);
+        let mut result = Self { rules, ..Self::default() };
+
+        let mut fields = s.trim().trim_end_matches('.').split(':');
+        result.turn = match fields.next() {
+            Some("W") => Color::White,
+            Some("B") => Color::Black,
+            _ => return Err(invalid())
         };
-        result.promote_pawns();
+        for field in fields {
+            let color = match field.get(..1) {
+                Some("W") => Color::White,
+                Some("B") => Color::Black,
+                _ => return Err(invalid())
+            };
+            for entry in field[1..].split(',').filter(|entry| !entry.is_empty()) {
+                let (king, range) = match entry.strip_prefix('K') {
+                    Some(range) => (true, range),
+                    None => (false, entry)
+                };
+                let number = |n: &str| n.parse::<usize>().ok().filter(|&n| result.numbered_square(n).is_some()).ok_or_else(|| ParseError::invalid("square number", n));
+                let (first, last) = match range.split_once('-') {
+                    Some((first, last)) => (number(first)?, number(last)?),
+                    None => (number(range)?, number(range)?)
+                };
+                for n in first..=last {
+                    let cp = result.numbered_square(n).unwrap();
+                    if result[cp].piece.is_some() {
+                        return Err(ParseError::invalid("FEN with each square once", s));
+                    }
+                    result.set(cp, Some(Piece {king, color}));
+                }
+            }
+        }
+        result.recompute_hash();
         result.find_forced_jumps();
-        result
+        Ok(result)
+    }
+
+    /// The position as a PDN FEN string; the side to move is included, a capture in progress is not.
+    pub fn fen(&self) -> String {
+        let side = |color| {
+            let mut pieces = self.piece_pos(color).into_iter().map(|cp| (self.square_number(cp).unwrap(), self[cp].piece.unwrap().king)).collect::<Vec<_>>();
+            pieces.sort();
+            pieces.into_iter().map(|(n, king)| if king { format!("K{}", n) } else { n.to_string() }).collect::<Vec<_>>().join(",")
+        };
+        let turn = if self.turn == Color::White { "W" } else { "B" };
+        format!("{}:W{}:B{}", turn, side(Color::White), side(Color::Black))
     }
 
-    fn promote_pawns(&mut self) {
-        for i in 0..8 {
-            let tcp = CellPos {row: 7, col: i};
-            if let Some(piece) = &mut self[tcp].piece {
-                if piece.color == Color::White {
-                    piece.king = true;
+    /// The legal move of the side to move here that leaves the pieces as in `after`, whoever is to move there.
+    pub fn played_move(&self, after: &Board) -> Option<Move> {
+        if self.size() != after.size() {
+            return None;
+        }
+        self.legal_moves().into_iter().find(|mv| {
+            let mut board = self.clone();
+            board.apply_unchecked(mv);
+            (board.white, board.black, board.kings) == (after.white, after.black, after.kings)
+        })
+    }
+
+    /// Reconstructs the move played between two consecutive boards, with every square it lands on
+    /// so that it can be replayed with `make_move_to` even when a king flies several squares.
+    pub fn diff_to_move(before: &Board, after: &Board) -> Option<Move> {
+        before.played_move(after)
+    }
+
+    /// The same position seen from the other side: turned by 180 degrees, with colours and the side to move swapped.
+    /// Moves, captures and evaluation of the side to move stay the same.
+    pub fn flip_colors(&self) -> Self {
+        let geo = self.geo();
+        let last = self.size()-1;
+        let mut res = Self {
+            must_jump: self.must_jump.iter().map(|cp| cell(last-cp.row, last-cp.col)).collect(),
+            turn: -self.turn,
+            white: geo.rotate(self.black),
+            black: geo.rotate(self.white),
+            kings: geo.rotate(self.kings),
+            captured: geo.rotate(self.captured),
+            ..self.clone()
+        };
+        res.recompute_hash();
+        res
+    }
+
+    /// `flip_colors` keeping the side to move: the same pieces with the other player to move,
+    /// so a score for the side to move changes sign. Forced jumps are found again for the new mover.
+    pub fn swap_colors(&self) -> Self {
+        let mut res = Self {
+            turn: self.turn,
+            ..self.flip_colors()
+        };
+        res.find_forced_jumps();
+        res.recompute_hash();
+        res
+    }
+
+    /// Checks a position read from outside for things no game can reach. Pieces on light squares
+    /// cannot be represented and are already rejected when parsing.
+    pub fn validate(&self) -> Result<(), Vec<PositionError>> {
+        let geo = self.geo();
+        let mut errors = vec![];
+        let limit = geo.start_rows(Color::White).count_ones() as usize;
+        for color in [Color::White, Color::Black] {
+            let count = self.pieces(color).count_ones() as usize;
+            if count > limit {
+                errors.push(PositionError::TooManyPieces { color, count });
+            }
+        }
+
+        // Only a man in the middle of a capture that crowns at its end may stand on the last row.
+        let continuing = |cp: CellPos| self.must_jump == [cp] && self.rules.promotion() == Promotion::AtEnd;
+        for color in [Color::White, Color::Black] {
+            for cp in geo.squares(self.pieces(color) & !self.kings & geo.promotion_row(color)) {
+                if !continuing(cp) {
+                    errors.push(PositionError::UncrownedOnPromotionRow(cp));
                 }
             }
-            let bcp = CellPos {row: 0, col: i};
-            if let Some(piece) = &mut self[bcp].piece {
-                if piece.color == Color::Black {
-                    piece.king = true;
+        }
+
+        let jumpers = self.jumpers();
+        for &cp in &self.must_jump {
+            match geo.square(cp) {
+                Some(sq) if self.pieces(self.turn) & (1 << sq) != 0 => {
+                    if jumpers & (1 << sq) == 0 {
+                        errors.push(PositionError::MustJumpWithoutCapture(cp));
+                    }
                 }
+                _ => errors.push(PositionError::MustJumpWithoutPiece(cp))
+            }
+        }
+        if self.must_jump.len() != 1 {
+            let mut forced = self.clone();
+            forced.find_forced_jumps();
+            let same = forced.must_jump.len() == self.must_jump.len() && forced.must_jump.iter().all(|cp| self.must_jump.contains(cp));
+            if !same {
+                errors.push(PositionError::ForcedJumpsMismatch { expected: forced.must_jump });
             }
         }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
     }
 
     pub fn piece_pos(&self, color: Color) -> Vec<CellPos> {
+        self.geo().squares(self.pieces(color)).collect()
+    }
+
+    /// Every legal move of the side to move, with multi-jumps as a single `Move`.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        if !self.must_jump.is_empty() {
+            return self.capture_moves(&self.must_jump);
+        }
         let mut result = vec![];
-        for i in 0..8 {
-            for j in 0..8 {
-                let cp = CellPos {row: i, col: j};
-                let Some(piece) = self[cp].piece else {
-                    continue;
-                };
-                if piece.color == color {
-                    result.push(cp);
+        let own = self.pieces(self.turn);
+        let empty = self.empty();
+        let flying = self.rules.flying_kings();
+        for dir in DIRS {
+            let mut pieces = if dir.is_forward(self.turn) { own } else { own & self.kings };
+            if flying {
+                pieces &= !self.kings;
+            }
+            for cp in self.geo().squares(self.geo().shift(empty, dir.opposite()) & pieces) {
+                result.push(Move {from: cp, path: vec![cp.shift_on(dir, self.size()).unwrap()], captures: vec![]});
+            }
+        }
+        if flying {
+            for sq in bits(own & self.kings) {
+                for dir in DIRS {
+                    let mut next = self.geo().step(sq, dir);
+                    while let Some(to) = next {
+                        if empty & (1 << to) == 0 {
+                            break;
+                        }
+                        result.push(Move {from: self.geo().square_pos(sq), path: vec![self.geo().square_pos(to)], captures: vec![]});
+                        next = self.geo().step(to, dir);
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// The capture sequences of the pieces on `from`, filtered by the capture priority of the rules.
+    fn capture_moves(&self, from: &[CellPos]) -> Vec<Move> {
+        let mut result = vec![];
+        for &cp in from {
+            let sq = self.geo().square(cp).unwrap();
+            let mut mv = Move {from: cp, path: vec![], captures: vec![]};
+            let occupied = (self.white | self.black) & !(1 << sq);
+            self.collect_captures(sq, self.kings & (1 << sq) != 0, occupied, self.captured, &mut mv, &mut result);
+        }
+        match self.rules.capture_priority() {
+            CapturePriority::Free | CapturePriority::KingsFirst => {}
+            CapturePriority::Maximum => {
+                let most = result.iter().map(|mv| mv.captures.len()).max().unwrap_or(0);
+                result.retain(|mv| mv.captures.len() == most);
+            }
+            CapturePriority::Italian => {
+                let king_captures = |mv: &Move| mv.captures.iter().filter(|&&cp| self[cp].piece.unwrap().king).count();
+                let first_king = |mv: &Move| mv.captures.iter().position(|&cp| self[cp].piece.unwrap().king).unwrap_or(usize::MAX);
+                let key = |mv: &Move| (mv.captures.len(), self[mv.from].piece.unwrap().king, king_captures(mv), std::cmp::Reverse(first_king(mv)));
+                if let Some(best) = result.iter().map(key).max() {
+                    result.retain(|mv| key(mv) == best);
                 }
             }
         }
         result
     }
-}
 
+    /// Depth-first search over the captures of a piece standing on `sq`.
+    ///
+    /// `occupied` excludes the moving piece; `captured` holds the pieces already jumped in this move.
+    fn collect_captures(&self, sq: usize, king: bool, occupied: u64, captured: u64, mv: &mut Move, result: &mut Vec<Move>) {
+        let rules = self.rules;
+        let mut targets = self.pieces(-self.turn) & !captured;
+        if !king && !rules.men_capture_kings() {
+            targets &= !self.kings;
+        }
+        let flying = king && rules.flying_kings();
+        let mut found = false;
+        for dir in DIRS {
+            if !king && !rules.men_capture_backwards() && !dir.is_forward(self.turn) {
+                continue;
+            }
+            let mut next = self.geo().step(sq, dir);
+            if flying {
+                while let Some(s) = next.filter(|&s| occupied & (1 << s) == 0) {
+                    next = self.geo().step(s, dir);
+                }
+            }
+            let Some(target) = next else {
+                continue;
+            };
+            if targets & (1 << target) == 0 {
+                continue;
+            }
+            let ncaptured = captured | (1 << target);
+            let noccupied = if rules.removes_captures_at_end() { occupied } else { occupied & !(1 << target) };
+            let mut landing = self.geo().step(target, dir);
+            while let Some(to) = landing {
+                if occupied & (1 << to) != 0 {
+                    break;
+                }
+                found = true;
+                mv.path.push(self.geo().square_pos(to));
+                mv.captures.push(self.geo().square_pos(target));
+                let crowned = !king && self.geo().promotion_row(self.turn) & (1 << to) != 0;
+                match (crowned, rules.promotion()) {
+                    (true, Promotion::EndsMove) => result.push(mv.clone()),
+                    (true, Promotion::Immediate) => self.collect_captures(to, true, noccupied, ncaptured, mv, result),
+                    _ => self.collect_captures(to, king, noccupied, ncaptured, mv, result)
+                }
+                mv.path.pop();
+                mv.captures.pop();
+                if !flying {
+                    break;
+                }
+                landing = self.geo().step(to, dir);
+            }
+        }
+        if !found && !mv.path.is_empty() {
+            result.push(mv.clone());
+        }
+    }
+
+    /// Plays a whole move; returns `None` and leaves the board untouched if it is not in `legal_moves`.
+    pub fn apply(&mut self, mv: &Move) -> Option<MoveUndo> {
+        if !self.legal_moves().contains(mv) {
+            return None;
+        }
+        Some(self.apply_unchecked(mv))
+    }
+
+    /// Plays a move taken from `legal_moves` of this very position without checking it again.
+    pub fn apply_unchecked(&mut self, mv: &Move) -> MoveUndo {
+        let undo = self.undo_record();
+        let mut piece = self[mv.from].piece.unwrap();
+        self.set(mv.from, None);
+        for &cp in &mv.captures {
+            self.set(cp, None);
+        }
+        for cp in self.geo().squares(self.captured) {
+            self.set(cp, None);
+        }
+        self.set_captured(0);
+        if !piece.king {
+            let crowning = self.geo().promotion_row(self.turn);
+            let on_row = |cp: &CellPos| crowning & (1 << self.geo().square(*cp).unwrap()) != 0;
+            piece.king = match self.rules.promotion() {
+                Promotion::Immediate => mv.path.iter().any(on_row),
+                Promotion::EndsMove | Promotion::AtEnd => on_row(&mv.to())
+            };
+        }
+        self.set(mv.to(), Some(piece));
+        self.pass_turn();
+        self.find_forced_jumps();
+
+        debug_assert_eq!(self.hash, self.full_hash(), "Incremental hash diverged from full recomputation.");
+
+        undo
+    }
+
+    /// Finds the legal move written as squares joined by `-` or `x`, as printed by `Display for Move`.
+    pub fn parse_move(&self, s: &str) -> Result<Move, ParseError> {
+        let squares = s.split(['-', 'x']).map(|sq| sq.parse::<CellPos>()).collect::<Result<Vec<_>, _>>()?;
+        let Some((&from, path)) = squares.split_first() else {
+            return Err(ParseError::MissingInput { expected: "move" });
+        };
+        self.legal_moves().into_iter().find(|mv| mv.from == from && mv.path == path).ok_or_else(|| ParseError::invalid("legal move", s))
+    }
+
+    /// Writes `mv` in numeric notation: `11-15`, or every landing square of a capture (`22x15x8`).
+    pub fn numeric_move(&self, mv: &Move) -> String {
+        let sep = if mv.is_capture() { "x" } else { "-" };
+        let number = |cp| self.square_number(cp).unwrap().to_string();
+        std::iter::once(mv.from).chain(mv.path.iter().copied()).map(number).collect::<Vec<_>>().join(sep)
+    }
+
+    /// Parses a legal move in numeric notation; a capture may give only its start and end squares if that is unambiguous.
+    pub fn parse_numeric_move(&self, s: &str) -> Result<Move, ParseError> {
+        let squares = s.split(['-', 'x']).map(|n| self.parse_square(n, Notation::Numeric)).collect::<Result<Vec<_>, _>>()?;
+        let Some((&from, path)) = squares.split_first() else {
+            return Err(ParseError::MissingInput { expected: "move" });
+        };
+        let moves = self.legal_moves();
+        if let Some(mv) = moves.iter().find(|mv| mv.from == from && mv.path == path) {
+            return Ok(mv.clone());
+        }
+        let mut ends = moves.into_iter().filter(|mv| path.len() == 1 && mv.from == from && mv.to() == path[0]);
+        match (ends.next(), ends.next()) {
+            (Some(mv), None) => Ok(mv),
+            _ => Err(ParseError::invalid("legal move", s))
+        }
+    }
+}
 
 pub fn heuristic(board: &Board) -> i64 {
     let mut res = 0;
 
-    let row_vals_pawn = [7, 0, 1, 2, 3, 4, 5, 9];
-    let row_vals_king = [1, 2, 2, 3, 3, 2, 2, 1];
+    let size = board.size();
+    // Back row guards against crowning, the last row is about to promote.
+    let row_val_pawn = |rrow: usize| if rrow == 0 {7} else if rrow == size-1 {9} else {rrow as i64 - 1};
+    let row_val_king = |rrow: usize| 1 + (rrow.min(size-1-rrow) as i64 + 1)/2;
     let sq_6x6_val = 3;
     let sq_4x4_val = 1;
     let piece_val = 5;
@@ -513,43 +1422,44 @@ pub fn heuristic(board: &Board) -> i64 {
     let exposed_pawn_val = -2;
     let trn_jump_val = 3;
 
+    // Counted for both sides, so that the score only changes sign when the other side is to move.
+    let jumpers = board.jumpers_of(Color::White) | board.jumpers_of(Color::Black);
+
     for color in [Color::White, Color::Black] {
         let mp = if board.turn == color {1} else {-1};
         let mut lres = 0;
 
         for cp in board.piece_pos(color) {
             let piece = board[cp].piece.unwrap();
-            let rrow = if color == Color::White {cp.row} else { 8-cp.row-1 };
+            let rrow = if color == Color::White {cp.row} else { size-cp.row-1 };
 
             lres += piece_val;
 
-            if cp.col >= 1 && cp.col <= 6 && cp.row >= 1 && cp.row <= 6 {
+            if cp.col >= 1 && cp.col <= size-2 && cp.row >= 1 && cp.row <= size-2 {
                 lres += sq_6x6_val;
-                if cp.col >= 2 && cp.col <= 5 && cp.row >= 2 && cp.row <= 5 {
+                if cp.col >= 2 && cp.col <= size-3 && cp.row >= 2 && cp.row <= size-3 {
                     lres += sq_4x4_val;
                 }
             }
 
-            if color == board.turn {
-                if board.can_jump(cp) {
-                    lres += trn_jump_val;
-                }
+            if jumpers & (1 << board.geo().square(cp).unwrap()) != 0 {
+                lres += trn_jump_val;
             }
 
             if piece.king {
                 lres += king_val;
-                lres += row_vals_king[rrow];
+                lres += row_val_king(rrow);
             }
             else {
-                lres += row_vals_pawn[rrow];
+                lres += row_val_pawn(rrow);
                 let (ls, rs) = if color == Color::White {(MoveDir::TopLeft, MoveDir::TopRight)} else {(MoveDir::DownLeft, MoveDir::DownRight)};
-                let lnpos = cp.shift(ls);
-                let rnpos = cp.shift(rs);
+                let lnpos = cp.shift_on(ls, size);
+                let rnpos = cp.shift_on(rs, size);
                 let (b_ls, b_rs) = if color == Color::White {(MoveDir::DownLeft, MoveDir::DownRight)} else {(MoveDir::TopLeft, MoveDir::TopRight)};
     
                 // Checking for exposed pawn in both backward directions
                 for back_dir in [b_ls, b_rs] {
-                    let back_pos = cp.shift(back_dir);
+                    let back_pos = cp.shift_on(back_dir, size);
                     if let Some(back_pos) = back_pos {
                         if board[back_pos].piece.is_none() {
                             lres += exposed_pawn_val;
@@ -574,25 +1484,17 @@ pub fn heuristic(board: &Board) -> i64 {
     res
 }
 
-pub fn sort_by_heuristic<T: Fn(&Board) -> i64>(mut board: Board, poss: Vec<CellPos>, h_fn: T) -> Vec<CellPos> {
-    let old_board = board.clone();
-    let mut poss = poss.into_iter().map(|cp| {
-        let mut lbst = LOST;
-        for dir in DIRS {
-            let pm = PieceMove {pos: cp, dir};
-            if board.make_move(pm) {
-                let heur = heuristic(&board);
-                let score = if old_board.turn == board.turn {heur} else {-heur};
-                lbst = lbst.max(score);
-                board = old_board.clone();
-            }
-        }
-        (lbst, cp)
+pub fn sort_by_heuristic<T: Fn(&Board) -> i64>(board: &mut Board, moves: Vec<Move>, h_fn: T) -> Vec<Move> {
+    let mut moves = moves.into_iter().map(|mv| {
+        let undo = board.apply_unchecked(&mv);
+        let score = -h_fn(board);
+        board.unmake_move(undo);
+        (score, mv)
     }).collect::<Vec<_>>();
 
-    poss.sort_by_key(|x| x.0);
-    poss.reverse();
-    poss.into_iter().map(|(_, cp)| cp).collect::<Vec<_>>()
+    moves.sort_by_key(|x| x.0);
+    moves.reverse();
+    moves.into_iter().map(|(_, mv)| mv).collect::<Vec<_>>()
 }
 
 
@@ -1,47 +1,192 @@
-use std::{io::{stdin, Stdin}, ops::{IndexMut, Index, Neg}, fmt::Display, error::Error};
+use std::{io::{stdin, BufRead, Stdin}, error::Error, time::Duration};
 use checkers::*;
 
-mod ai;
-use ai::AI;
-
+/// The next line without surrounding whitespace, or None at the end of the input.
+/// A line that is not UTF-8 is answered with `error <reason>` and skipped.
+fn next_line(stdin: &Stdin) -> Result<Option<String>, Box<dyn Error>> {
+    loop {
+        let mut bytes = vec![];
+        if stdin.lock().read_until(b'\n', &mut bytes)? == 0 {
+            return Ok(None);
+        }
+        match String::from_utf8(bytes) {
+            Ok(line) => return Ok(Some(line.trim().into())),
+            Err(err) => println!("error {}", err)
+        }
+    }
+}
 
 fn main() -> Result<(), Box<dyn Error>> {
     let stdin = stdin();
     let mut ai = AI::new();
 
-    let mut color_str = String::new();
-    stdin.read_line(&mut color_str)?;
-    color_str = color_str.trim().into();
-
-    let mut color = Color::from_str(&color_str);
+    // Lines before the first colour are answered with an error, as there is no side to play yet.
+    let mut color = loop {
+        let Some(line) = next_line(&stdin)? else {
+            return Ok(());
+        };
+        match line.parse::<Color>() {
+            Ok(color) => break color,
+            Err(err) => println!("error {}", err)
+        }
+    };
+    // Set by a `rules <name>` command; from then on whole moves are sent instead of single steps.
+    let mut rules = None;
+    // Set by a `notation <name>` command; numeric squares and moves are always whole moves.
+    let mut notation = Notation::Algebraic;
+    // Set by a `time <milliseconds>` command; how long to think about each move.
+    let mut budget = Duration::from_secs(1);
+    // The game as far as it can be followed from the boards we are sent.
+    let mut record: Option<GameRecord> = None;
+    // The board after the steps sent so far of our own unfinished capture; the record takes the move once it is complete.
+    let mut partial: Option<Board> = None;
 
     loop {
-        let mut inp = String::new();
-        stdin.read_line(&mut inp).unwrap();
-        inp = inp.trim().into();
-        if inp == "white" || inp == "black" {
-            color = Color::from_str(&inp);
+        let Some(inp) = next_line(&stdin)? else {
+            break Ok(());
+        };
+        if let Ok(new_color) = inp.parse::<Color>() {
+            color = new_color;
             continue;
         }
         if inp == "exit" {
             break Ok(());
         }
-        let must_jump = inp.split_whitespace().map(|pstr| {
-            CellPos::from_str(pstr)
-        }).collect::<Vec<_>>();
+        if let Some(name) = inp.strip_prefix("rules ") {
+            match name.parse::<RuleSet>() {
+                Ok(r) => rules = Some(r),
+                Err(err) => eprintln!("Ignoring command: {}", err)
+            }
+            continue;
+        }
+        if let Some(millis) = inp.strip_prefix("time ") {
+            match millis.parse() {
+                Ok(millis) => budget = Duration::from_millis(millis),
+                Err(err) => eprintln!("Ignoring command: {}", err)
+            }
+            continue;
+        }
+        if let Some(depth) = inp.strip_prefix("depth ") {
+            match depth.parse() {
+                // Stops deepening there even with time left.
+                Ok(depth) => ai.set_max_depth(depth),
+                Err(err) => eprintln!("Ignoring command: {}", err)
+            }
+            continue;
+        }
+        if let Some(threads) = inp.strip_prefix("threads ") {
+            match threads.parse() {
+                Ok(threads) => ai.set_threads(threads),
+                Err(err) => eprintln!("Ignoring command: {}", err)
+            }
+            continue;
+        }
+        if let Some(path) = inp.strip_prefix("tablebase ") {
+            match Tablebase::load(path) {
+                Ok(tablebase) => ai.set_tablebase(tablebase),
+                Err(err) => eprintln!("Ignoring command: {}", err)
+            }
+            continue;
+        }
+        if let Some(path) = inp.strip_prefix("book ") {
+            match OpeningBook::load(path) {
+                Ok(book) => ai.set_book(book),
+                Err(err) => eprintln!("Ignoring command: {}", err)
+            }
+            continue;
+        }
+        if let Some(name) = inp.strip_prefix("notation ") {
+            match name.parse() {
+                Ok(n) => notation = n,
+                Err(err) => eprintln!("Ignoring command: {}", err)
+            }
+            continue;
+        }
+        // A position that cannot be used is answered with `error <reason>` instead of a move, so the controller is not left waiting.
 
-        // eprintln!("Reading board from stdin...");
-        let mut board = Board::from_stdin(&stdin);
-        board.turn = color;
-        board.must_jump = must_jump;
-        board.recompute_hash();
+        // A one-line position with its own side to move, instead of the must_jump line and grid.
+        let board = if let Some(fen) = inp.strip_prefix("fen ") {
+            match Board::from_fen(fen, rules.unwrap_or_default()) {
+                Ok(board) => board,
+                Err(err) => {
+                    println!("error {}", err);
+                    continue;
+                }
+            }
+        }
+        else {
+            // eprintln!("Reading board from stdin...");
+            let board = match rules {
+                Some(rules) => Board::read_with_rules(&mut stdin.lock(), rules),
+                None => Board::read_from(&mut stdin.lock())
+            };
+            // Square numbers depend on the board size, so the must_jump line is parsed once the grid is read.
+            let must_jump = board.as_ref().map_or(Ok(vec![]), |board| inp.split_whitespace().map(|pstr| {
+                board.parse_square(pstr, notation)
+            }).collect::<Result<Vec<_>, _>>());
+            let (mut board, must_jump) = match (board, must_jump) {
+                (Ok(board), Ok(must_jump)) => (board, must_jump),
+                (Err(err), _) | (_, Err(err)) => {
+                    println!("error {}", err);
+                    continue;
+                }
+            };
+            board.turn = color;
+            board.must_jump = must_jump;
+            board.recompute_hash();
+            board
+        };
+        if let Err(errors) = board.validate() {
+            println!("error {}", describe_errors(&errors));
+            continue;
+        }
+
+        // Continue the record with the opponent's move, or leave it while our own capture is under way;
+        // start over if the board does not follow from it.
+        let continues = partial.take().is_some_and(|partial| partial.hash == board.hash);
+        let followed = record.as_mut().is_some_and(|record| {
+            if continues || record.board().hash == board.hash {
+                return true;
+            }
+            let played = record.board().played_move(&board);
+            played.is_some_and(|mv| record.play(&mv)) && record.board().hash == board.hash
+        });
+        let record = match &mut record {
+            Some(record) if followed => record,
+            _ => record.insert(GameRecord::new(board.clone()))
+        };
+        ai.set_history((0..=record.ply()).filter_map(|ply| record.hash_at(ply)).collect());
 
         // eprintln!("Parsed board: \n{}", board);
 
         // eprintln!("Computing best move...");
-        let mv = ai.compute_move(&board);
+        // Without a legal move the game is lost, which is answered instead of a move.
+        let Some(mv) = ai.compute_move(&board, budget) else {
+            println!("resign");
+            continue;
+        };
 
-        println!("{}", mv);
+        if notation == Notation::Numeric {
+            println!("{}", board.numeric_move(&mv));
+            record.play(&mv);
+        }
+        else if rules.is_some() {
+            println!("{}", mv);
+            record.play(&mv);
+        }
+        else {
+            // Only the first step is sent; the rest of a capture is chosen once its board comes back.
+            let step = mv.steps()[0];
+            println!("{}", step);
+            let mut after = board.clone();
+            after.make_move(step);
+            if after.turn == board.turn {
+                partial = Some(after);
+            }
+            else if let Some(played) = record.board().played_move(&after) {
+                record.play(&played);
+            }
+        }
         // eprintln!("Printed move to stdout.");
     }
 }
\ No newline at end of file
@@ -0,0 +1,313 @@
+use super::*;
+
+/// How a PDN game ended; scores are written from the point of view of the side that moves first under the rules.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PdnResult {
+    Win(Color),
+    Draw,
+    Unfinished
+}
+
+impl From<Option<Outcome>> for PdnResult {
+    fn from(outcome: Option<Outcome>) -> Self {
+        match outcome {
+            Some(Outcome::Win(color)) => PdnResult::Win(color),
+            Some(Outcome::Draw(_)) => PdnResult::Draw,
+            None => PdnResult::Unfinished
+        }
+    }
+}
+
+/// A game in Portable Draughts Notation: tags, the start position and the moves played from it.
+#[derive(Clone)]
+pub struct PdnGame {
+    /// Tags other than `GameType`, `SetUp`, `FEN` and `Result`, which are kept in `start` and `result`.
+    pub tags: Vec<(String, String)>,
+    pub start: Board,
+    pub moves: Vec<Move>,
+    pub result: PdnResult
+}
+
+/// The PDN `GameType` number of `rules`; the original rules have none.
+fn game_type(rules: RuleSet) -> Option<u32> {
+    match rules {
+        RuleSet::Standard => None,
+        RuleSet::International => Some(20),
+        RuleSet::American => Some(21),
+        RuleSet::Italian => Some(22),
+        RuleSet::Pool => Some(23),
+        RuleSet::Russian => Some(25),
+        RuleSet::Brazilian => Some(26)
+    }
+}
+
+fn result_str(result: PdnResult, rules: RuleSet) -> &'static str {
+    match result {
+        PdnResult::Win(color) if color == rules.first_to_move() => "1-0",
+        PdnResult::Win(_) => "0-1",
+        PdnResult::Draw => "1/2-1/2",
+        PdnResult::Unfinished => "*"
+    }
+}
+
+fn parse_result(s: &str, rules: RuleSet) -> Option<PdnResult> {
+    let first = rules.first_to_move();
+    match s {
+        "1-0" | "2-0" => Some(PdnResult::Win(first)),
+        "0-1" | "0-2" => Some(PdnResult::Win(-first)),
+        "1/2-1/2" | "1-1" => Some(PdnResult::Draw),
+        "*" => Some(PdnResult::Unfinished),
+        _ => None
+    }
+}
+
+impl PdnGame {
+    pub fn new(start: Board) -> Self {
+        Self {
+            tags: vec![],
+            start,
+            moves: vec![],
+            result: PdnResult::Unfinished
+        }
+    }
+
+    /// The moves of `record` up to its cursor, with an unknown result.
+    pub fn from_record(record: &GameRecord) -> Self {
+        Self {
+            moves: record.moves().to_vec(),
+            ..Self::new(record.start().clone())
+        }
+    }
+
+    pub fn from_game(game: &Game) -> Self {
+        Self {
+            result: game.outcome().into(),
+            ..Self::from_record(game.record())
+        }
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(tag, _)| tag == name).map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string()))
+        }
+    }
+
+    /// The position after every move has been played.
+    pub fn board(&self) -> Board {
+        let mut board = self.start.clone();
+        for mv in &self.moves {
+            board.apply_unchecked(mv);
+        }
+        board
+    }
+}
+
+impl Display for PdnGame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rules = self.start.rules();
+        for (name, value) in &self.tags {
+            writeln!(f, "[{} \"{}\"]", name, value.replace('\\', "\\\\").replace('"', "\\\""))?;
+        }
+        if let Some(number) = game_type(rules) {
+            writeln!(f, "[GameType \"{}\"]", number)?;
+        }
+        let fen = self.start.fen();
+        if fen != Board::with_rules(rules).fen() {
+            writeln!(f, "[SetUp \"1\"]")?;
+            writeln!(f, "[FEN \"{}\"]", fen)?;
+        }
+        writeln!(f, "[Result \"{}\"]", result_str(self.result, rules))?;
+        writeln!(f)?;
+
+        let mut tokens = vec![];
+        let mut board = self.start.clone();
+        // Plies are counted from the first mover, so a game starting with the other side begins with `1...`.
+        let offset = usize::from(board.turn != rules.first_to_move());
+        for (ply, mv) in self.moves.iter().enumerate() {
+            let ply = ply + offset;
+            if ply%2 == 0 {
+                tokens.push(format!("{}.", ply/2 + 1));
+            }
+            else if tokens.is_empty() {
+                tokens.push(format!("{}...", ply/2 + 1));
+            }
+            tokens.push(board.numeric_move(mv));
+            board.apply_unchecked(mv);
+        }
+        tokens.push(result_str(self.result, rules).to_string());
+
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + token.len() >= 80 {
+                writeln!(f, "{}", line)?;
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        writeln!(f, "{}", line)
+    }
+}
+
+impl FromStr for PdnGame {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut games = parse_games(s)?;
+        match games.len() {
+            0 => Err(ParseError::MissingInput { expected: "game" }),
+            1 => Ok(games.remove(0)),
+            _ => Err(ParseError::invalid("single game", s))
+        }
+    }
+}
+
+enum Token<'a> {
+    Tag(&'a str),
+    Word(&'a str)
+}
+
+/// Splits PDN text into tags and movetext words, dropping comments and variations.
+fn tokenize(s: &str) -> Result<Vec<Token<'_>>, ParseError> {
+    let mut tokens = vec![];
+    let mut rest = s;
+    loop {
+        rest = rest.trim_start();
+        let Some(ch) = rest.chars().next() else {
+            return Ok(tokens);
+        };
+        let end = match ch {
+            '[' => {
+                let mut escaped = false;
+                let end = rest.char_indices().find(|&(_, c)| {
+                    let close = c == ']' && !escaped;
+                    escaped = c == '\\' && !escaped;
+                    close
+                });
+                let Some((end, _)) = end else {
+                    return Err(ParseError::MissingInput { expected: "end of tag" });
+                };
+                tokens.push(Token::Tag(&rest[1..end]));
+                end+1
+            }
+            '{' => rest.find('}').ok_or(ParseError::MissingInput { expected: "end of comment" })? + 1,
+            ';' => rest.find('\n').unwrap_or(rest.len()),
+            '(' => {
+                let mut depth = 0;
+                let end = rest.char_indices().find(|&(_, c)| {
+                    match c {
+                        '(' => depth += 1,
+                        ')' => depth -= 1,
+                        _ => {}
+                    }
+                    depth == 0
+                });
+                let Some((end, _)) = end else {
+                    return Err(ParseError::MissingInput { expected: "end of variation" });
+                };
+                end+1
+            }
+            _ => {
+                let end = rest.find(|c: char| c.is_whitespace() || "[{;(".contains(c)).unwrap_or(rest.len());
+                tokens.push(Token::Word(&rest[..end]));
+                end
+            }
+        };
+        rest = &rest[end..];
+    }
+}
+
+/// Splits the inside of `[Name "value"]` into its name and unescaped value.
+fn parse_tag(tag: &str) -> Result<(String, String), ParseError> {
+    let (name, value) = tag.trim().split_once(char::is_whitespace).ok_or_else(|| ParseError::invalid("tag", tag))?;
+    let value = value.trim().strip_prefix('"').and_then(|v| v.strip_suffix('"')).ok_or_else(|| ParseError::invalid("tag value", value))?;
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        unescaped.push(if c == '\\' { chars.next().unwrap_or(c) } else { c });
+    }
+    Ok((name.to_string(), unescaped))
+}
+
+/// Parses every game of a PDN file, replaying the moves to check they are legal.
+pub fn parse_games(s: &str) -> Result<Vec<PdnGame>, ParseError> {
+    let mut games = vec![];
+    let mut tags = vec![];
+    let mut words = vec![];
+    let mut in_moves = false;
+    for token in tokenize(s)? {
+        match token {
+            Token::Tag(tag) => {
+                if in_moves {
+                    games.push(build_game(std::mem::take(&mut tags), std::mem::take(&mut words), None)?);
+                    in_moves = false;
+                }
+                tags.push(parse_tag(tag)?);
+            }
+            Token::Word(word) => {
+                in_moves = true;
+                if parse_result(word, RuleSet::Standard).is_some() {
+                    games.push(build_game(std::mem::take(&mut tags), std::mem::take(&mut words), Some(word))?);
+                    in_moves = false;
+                }
+                else {
+                    words.push(word);
+                }
+            }
+        }
+    }
+    if in_moves || !tags.is_empty() {
+        games.push(build_game(tags, words, None)?);
+    }
+    Ok(games)
+}
+
+fn build_game(mut tags: Vec<(String, String)>, words: Vec<&str>, result: Option<&str>) -> Result<PdnGame, ParseError> {
+    let mut take_tag = |name: &str| tags.iter().position(|(tag, _)| tag == name).map(|i| tags.remove(i).1);
+    let rules = match take_tag("GameType") {
+        Some(value) => {
+            let number = value.split(',').next().and_then(|n| n.trim().parse().ok());
+            RuleSet::ALL.into_iter().find(|&rules| game_type(rules).is_some() && game_type(rules) == number)
+                .ok_or_else(|| ParseError::invalid("game type", &value))?
+        }
+        None => RuleSet::Standard
+    };
+    take_tag("SetUp");
+    let start = match take_tag("FEN") {
+        Some(fen) => Board::from_fen(&fen, rules)?,
+        None => Board::with_rules(rules)
+    };
+    let result_tag = take_tag("Result");
+    let result = match result.or(result_tag.as_deref()) {
+        Some(result) => parse_result(result, rules).ok_or_else(|| ParseError::invalid("result", result))?,
+        None => PdnResult::Unfinished
+    };
+
+    let mut game = PdnGame {
+        result,
+        ..PdnGame::new(start)
+    };
+    game.tags = tags;
+    let mut board = game.start.clone();
+    for word in words {
+        // Move numbers may be glued to the move (`1.11-15`) and moves may carry `!`/`?` annotations.
+        let word = match word.split_once('.') {
+            Some((number, mv)) if number.bytes().all(|b| b.is_ascii_digit()) => mv.trim_start_matches('.'),
+            _ => word
+        };
+        let word = word.trim_end_matches(['!', '?']);
+        if word.is_empty() || word.starts_with('$') {
+            continue;
+        }
+        let mv = board.parse_numeric_move(word)?;
+        board.apply_unchecked(&mv);
+        game.moves.push(mv);
+    }
+    Ok(game)
+}
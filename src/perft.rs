@@ -0,0 +1,74 @@
+use super::*;
+use std::sync::{Mutex, atomic::{AtomicUsize, Ordering}};
+
+/// Number of positions reached after `depth` whole moves (a multi-jump counts as one move).
+pub fn perft(board: &mut Board, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let moves = board.legal_moves();
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    let mut nodes = 0;
+    for mv in &moves {
+        let undo = board.apply_unchecked(mv);
+        nodes += perft(board, depth-1);
+        board.unmake_move(undo);
+    }
+    nodes
+}
+
+/// `perft` split by root move, in `legal_moves` order.
+pub fn divide(board: &Board, depth: u32) -> Vec<(Move, u64)> {
+    divide_parallel(board, depth, 1)
+}
+
+/// `divide` on `threads` threads. Root moves are split into their replies when there are fewer of them than threads.
+pub fn divide_parallel(board: &Board, depth: u32, threads: usize) -> Vec<(Move, u64)> {
+    let roots = board.legal_moves();
+    if depth == 0 {
+        return vec![];
+    }
+
+    // Each job is a root index and the position to count from, `left` plies above the leaves.
+    let mut jobs = vec![];
+    for (i, mv) in roots.iter().enumerate() {
+        let mut child = board.clone();
+        child.apply_unchecked(mv);
+        jobs.push((i, child));
+    }
+    let mut left = depth-1;
+    if jobs.len() < threads && left > 1 {
+        jobs = jobs.into_iter().flat_map(|(i, job)| {
+            job.legal_moves().into_iter().map(move |mv| {
+                let mut child = job.clone();
+                child.apply_unchecked(&mv);
+                (i, child)
+            }).collect::<Vec<_>>()
+        }).collect();
+        left -= 1;
+    }
+
+    let counts = Mutex::new(vec![0; roots.len()]);
+    let next = AtomicUsize::new(0);
+    std::thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            scope.spawn(|| {
+                while let Some((i, job)) = jobs.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let nodes = perft(&mut job.clone(), left);
+                    counts.lock().unwrap()[*i] += nodes;
+                }
+            });
+        }
+    });
+    roots.into_iter().zip(counts.into_inner().unwrap()).collect()
+}
+
+/// `perft` on `threads` threads.
+pub fn perft_parallel(board: &Board, depth: u32, threads: usize) -> u64 {
+    if depth <= 1 {
+        return perft(&mut board.clone(), depth);
+    }
+    divide_parallel(board, depth, threads).into_iter().map(|(_, nodes)| nodes).sum()
+}
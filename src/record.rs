@@ -0,0 +1,123 @@
+use super::*;
+
+/// The moves of a game from its start position, with a cursor that can be moved back and forth.
+#[derive(Clone)]
+pub struct GameRecord {
+    start: Board,
+    board: Board,
+    moves: Vec<Move>,
+    /// Hash of the position after each move.
+    hashes: Vec<u64>,
+    /// Undo records of the moves before the cursor.
+    undos: Vec<MoveUndo>
+}
+
+impl GameRecord {
+    pub fn new(start: Board) -> Self {
+        Self {
+            board: start.clone(),
+            start,
+            moves: vec![],
+            hashes: vec![],
+            undos: vec![]
+        }
+    }
+
+    pub fn start(&self) -> &Board {
+        &self.start
+    }
+
+    /// The position at the cursor.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Number of moves before the cursor.
+    pub fn ply(&self) -> usize {
+        self.undos.len()
+    }
+
+    /// Number of recorded moves, including those after the cursor.
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// The moves up to the cursor.
+    pub fn moves(&self) -> &[Move] {
+        &self.moves[..self.ply()]
+    }
+
+    /// Every recorded move, including those that can be redone.
+    pub fn all_moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// Hash of the position after `ply` moves (0 is the start position).
+    pub fn hash_at(&self, ply: usize) -> Option<u64> {
+        match ply {
+            0 => Some(self.start.hash),
+            _ => self.hashes.get(ply-1).copied()
+        }
+    }
+
+    /// Plays `mv` at the cursor, replacing the moves after it unless `mv` is the next one.
+    /// Returns false and leaves the record untouched if it is illegal.
+    pub fn play(&mut self, mv: &Move) -> bool {
+        if self.moves.get(self.ply()) == Some(mv) {
+            return self.redo();
+        }
+        let Some(undo) = self.board.apply(mv) else {
+            return false;
+        };
+        self.moves.truncate(self.ply());
+        self.hashes.truncate(self.ply());
+        self.moves.push(mv.clone());
+        self.hashes.push(self.board.hash);
+        self.undos.push(undo);
+        true
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let Some(undo) = self.undos.pop() else {
+            return false;
+        };
+        self.board.unmake_move(undo);
+        true
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let Some(mv) = self.moves.get(self.ply()) else {
+            return false;
+        };
+        let undo = self.board.apply_unchecked(mv);
+        self.undos.push(undo);
+        true
+    }
+
+    /// Moves the cursor to after `ply` moves; false if fewer moves are recorded.
+    pub fn goto(&mut self, ply: usize) -> bool {
+        if ply > self.len() {
+            return false;
+        }
+        while self.ply() > ply {
+            self.undo();
+        }
+        while self.ply() < ply {
+            self.redo();
+        }
+        true
+    }
+
+    /// The start position followed by the position after each recorded move.
+    pub fn positions(&self) -> impl Iterator<Item = Board> + '_ {
+        let mut board = self.start.clone();
+        std::iter::once(board.clone()).chain(self.moves.iter().map(move |mv| {
+            board.apply_unchecked(mv);
+            board.clone()
+        }))
+    }
+}
@@ -0,0 +1,115 @@
+use super::*;
+
+/// The draughts variant a `Board` is played under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RuleSet {
+    /// The rules this engine was written for: American checkers where kings that can capture must do so first.
+    #[default]
+    Standard,
+    American,
+    Russian,
+    Brazilian,
+    Pool,
+    Italian,
+    /// International draughts on a 10x10 board.
+    International
+}
+
+/// Which of the available captures a player may choose from.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum CapturePriority {
+    /// Any capture.
+    Free,
+    /// Only captures by kings if a king can capture.
+    KingsFirst,
+    /// Only the sequences capturing the most pieces.
+    Maximum,
+    /// Most pieces, then capturing with a king, then most kings captured, then capturing a king earliest.
+    Italian
+}
+
+/// What happens when a man reaches the far row.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Promotion {
+    /// It is crowned and the move ends there.
+    EndsMove,
+    /// It is crowned at once and keeps capturing as a king.
+    Immediate,
+    /// It keeps capturing as a man and is only crowned if the move ends on the far row.
+    AtEnd
+}
+
+impl RuleSet {
+    pub const ALL: [RuleSet; 7] = [RuleSet::Standard, RuleSet::American, RuleSet::Russian, RuleSet::Brazilian, RuleSet::Pool, RuleSet::Italian, RuleSet::International];
+
+    /// Rows and columns of the board.
+    pub fn size(self) -> usize {
+        match self {
+            RuleSet::International => 10,
+            _ => 8
+        }
+    }
+
+    /// Kings move and capture along whole diagonals.
+    pub fn flying_kings(self) -> bool {
+        matches!(self, RuleSet::Russian | RuleSet::Brazilian | RuleSet::Pool | RuleSet::International)
+    }
+
+    pub fn men_capture_backwards(self) -> bool {
+        matches!(self, RuleSet::Russian | RuleSet::Brazilian | RuleSet::Pool | RuleSet::International)
+    }
+
+    pub fn men_capture_kings(self) -> bool {
+        self != RuleSet::Italian
+    }
+
+    /// Captured pieces stay on the board until the move is complete, so they block and cannot be jumped twice.
+    pub fn removes_captures_at_end(self) -> bool {
+        self.flying_kings()
+    }
+
+    pub fn capture_priority(self) -> CapturePriority {
+        match self {
+            RuleSet::Standard => CapturePriority::KingsFirst,
+            RuleSet::American | RuleSet::Russian | RuleSet::Pool => CapturePriority::Free,
+            RuleSet::Brazilian | RuleSet::International => CapturePriority::Maximum,
+            RuleSet::Italian => CapturePriority::Italian
+        }
+    }
+
+    pub fn promotion(self) -> Promotion {
+        match self {
+            RuleSet::Standard | RuleSet::American | RuleSet::Italian => Promotion::EndsMove,
+            RuleSet::Russian => Promotion::Immediate,
+            RuleSet::Brazilian | RuleSet::Pool | RuleSet::International => Promotion::AtEnd
+        }
+    }
+
+    pub fn first_to_move(self) -> Color {
+        match self {
+            RuleSet::Standard | RuleSet::American | RuleSet::Pool => Color::Black,
+            RuleSet::Russian | RuleSet::Brazilian | RuleSet::Italian | RuleSet::International => Color::White
+        }
+    }
+}
+
+impl Display for RuleSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuleSet::Standard => write!(f, "standard"),
+            RuleSet::American => write!(f, "american"),
+            RuleSet::Russian => write!(f, "russian"),
+            RuleSet::Brazilian => write!(f, "brazilian"),
+            RuleSet::Pool => write!(f, "pool"),
+            RuleSet::Italian => write!(f, "italian"),
+            RuleSet::International => write!(f, "international")
+        }
+    }
+}
+
+impl FromStr for RuleSet {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RuleSet::ALL.into_iter().find(|rules| rules.to_string() == s).ok_or_else(|| ParseError::invalid("rule set", s))
+    }
+}
@@ -0,0 +1,108 @@
+//! Serde support behind the `serde` feature. Everything with a text form in the engine protocol serializes to that
+//! string (`"white"`, `"C2"`, `"C2 tr"`, `"american"`); pieces use the grid letters and boards their FEN.
+
+use super::*;
+use serde::{Serialize, Serializer, Deserialize, Deserializer, de::Error};
+
+macro_rules! via_string {
+    ($($ty:ty),*) => {$(
+        impl Serialize for $ty {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer)?.parse().map_err(D::Error::custom)
+            }
+        }
+    )*};
+}
+
+via_string!(Color, CellPos, MoveDir, PieceMove, RuleSet, Notation);
+
+impl Serialize for Piece {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let letter = match (self.color, self.king) {
+            (Color::White, false) => "w",
+            (Color::Black, false) => "b",
+            (Color::White, true) => "W",
+            (Color::Black, true) => "B"
+        };
+        serializer.serialize_str(letter)
+    }
+}
+
+impl<'de> Deserialize<'de> for Piece {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let letter = String::deserialize(deserializer)?;
+        match letter.as_str() {
+            "w" => Ok(Piece {king: false, color: Color::White}),
+            "b" => Ok(Piece {king: false, color: Color::Black}),
+            "W" => Ok(Piece {king: true, color: Color::White}),
+            "B" => Ok(Piece {king: true, color: Color::Black}),
+            _ => Err(D::Error::custom(ParseError::invalid("piece", &letter)))
+        }
+    }
+}
+
+/// An empty cell is `null`, otherwise the piece on it.
+impl Serialize for Cell {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.piece.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Cell {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Cell {piece: Option::deserialize(deserializer)?})
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct BoardRepr {
+    rules: RuleSet,
+    fen: String,
+    /// Only present while a capture is in progress.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    must_jump: Vec<CellPos>,
+    /// Pieces already jumped in the capture in progress that stay on the board until it ends.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    captured: Vec<CellPos>
+}
+
+impl Serialize for Board {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BoardRepr {
+            rules: self.rules,
+            fen: self.fen(),
+            must_jump: self.must_jump.clone(),
+            captured: self.geo().squares(self.captured).collect()
+        }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = BoardRepr::deserialize(deserializer)?;
+        let mut board = Board::from_fen(&repr.fen, repr.rules).map_err(D::Error::custom)?;
+        if !repr.must_jump.is_empty() {
+            // Move generation relies on every forced square holding a piece of the side to move.
+            for &cp in &repr.must_jump {
+                if board.geo().square(cp).is_none() || !board[cp].piece.is_some_and(|piece| piece.color == board.turn) {
+                    return Err(D::Error::custom(ParseError::invalid("must jump square", &cp.to_string())));
+                }
+            }
+            board.must_jump = repr.must_jump;
+        }
+        for cp in repr.captured {
+            match board.geo().square(cp) {
+                Some(sq) if board[cp].piece.is_some() => board.captured |= 1 << sq,
+                _ => return Err(D::Error::custom(ParseError::invalid("captured piece", &cp.to_string())))
+            }
+        }
+        board.recompute_hash();
+        Ok(board)
+    }
+}
@@ -0,0 +1,583 @@
+use super::*;
+use std::{collections::HashMap, io::{self, Read, Write}, path::Path};
+
+/// Most pieces a tablebase can be generated for: five take minutes to solve and about 200 MB with the distances,
+/// six would take hours and gigabytes.
+pub const MAX_PIECES: u32 = 5;
+
+/// Numbers of each kind of piece in a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Material {
+    pub white_men: u32,
+    pub white_kings: u32,
+    pub black_men: u32,
+    pub black_kings: u32
+}
+
+impl Material {
+    pub fn of(board: &Board) -> Self {
+        Self {
+            white_men: (board.white & !board.kings).count_ones(),
+            white_kings: (board.white & board.kings).count_ones(),
+            black_men: (board.black & !board.kings).count_ones(),
+            black_kings: (board.black & board.kings).count_ones()
+        }
+    }
+
+    pub fn pieces(&self) -> u32 {
+        self.men() + self.white_kings + self.black_kings
+    }
+
+    pub fn men(&self) -> u32 {
+        self.white_men + self.black_men
+    }
+
+    /// The material with the colours swapped.
+    pub fn flipped(self) -> Self {
+        Self {
+            white_men: self.black_men,
+            white_kings: self.black_kings,
+            black_men: self.white_men,
+            black_kings: self.white_kings
+        }
+    }
+
+    /// Every material with one to `pieces` pieces a side and at most `pieces` in total, in the order they are solved:
+    /// captures and promotions only lead to materials earlier in the list.
+    pub fn all(pieces: u32) -> Vec<Self> {
+        let mut res = vec![];
+        for n in 2..=pieces {
+            for men in 0..=n {
+                for white in 1..n {
+                    for white_men in 0..=white.min(men) {
+                        let black_men = men - white_men;
+                        if black_men <= n - white {
+                            res.push(Self {
+                                white_men,
+                                white_kings: white - white_men,
+                                black_men,
+                                black_kings: n - white - black_men
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        res
+    }
+
+    /// The piece groups in index order, white men, black men, white kings and black kings,
+    /// with how many squares they can stand on: men off their promotion row, kings on the squares left free.
+    fn groups(&self) -> [(u32, usize); 4] {
+        let men = self.men() as usize;
+        [(self.white_men, 28), (self.black_men, 28), (self.white_kings, 32 - men), (self.black_kings, 32 - men - self.white_kings as usize)]
+    }
+
+    /// Number of indices of positions with White to move, a few of which put a white and a black man on one square.
+    fn table_len(&self) -> usize {
+        self.groups().iter().map(|&(count, squares)| binomial(squares, count as usize)).product()
+    }
+}
+
+impl Display for Material {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}{}{}", "K".repeat(self.white_kings as usize), "M".repeat(self.white_men as usize), "k".repeat(self.black_kings as usize), "m".repeat(self.black_men as usize))
+    }
+}
+
+/// The result of a position with perfect play, for the side to move.
+/// Distances are the plies until the losing side is left without a move, if the tablebase keeps them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TablebaseValue {
+    Win(Option<u32>),
+    Loss(Option<u32>),
+    Draw
+}
+
+impl TablebaseValue {
+    /// Entries are 0 for a draw and otherwise one more than the distance, which is odd for wins and even for losses.
+    fn from_entry(entry: u16) -> Self {
+        match entry {
+            0 => Self::Draw,
+            _ if entry%2 == 1 => Self::Loss(Some(entry as u32 - 1)),
+            _ => Self::Win(Some(entry as u32 - 1))
+        }
+    }
+}
+
+const fn binomials() -> [[usize; MAX_PIECES as usize + 1]; 33] {
+    let mut res = [[0; MAX_PIECES as usize + 1]; 33];
+    let mut n = 0;
+    while n <= 32 {
+        res[n][0] = 1;
+        let mut k = 1;
+        while k <= MAX_PIECES as usize && k <= n {
+            res[n][k] = res[n-1][k-1] + if k < n { res[n-1][k] } else { 0 };
+            k += 1;
+        }
+        n += 1;
+    }
+    res
+}
+
+static BINOMIAL: [[usize; MAX_PIECES as usize + 1]; 33] = binomials();
+
+fn binomial(n: usize, k: usize) -> usize {
+    BINOMIAL[n][k]
+}
+
+/// Squares white and black men can stand on.
+const WHITE_MEN: u64 = 0x0FFF_FFFF;
+const BLACK_MEN: u64 = 0xFFFF_FFF0;
+const SQUARES: u64 = 0xFFFF_FFFF;
+
+/// The squares of `set` renumbered among `squares` only.
+fn compress(set: u64, squares: u64) -> u64 {
+    bits(squares).enumerate().filter(|&(_, sq)| set & (1 << sq) != 0).fold(0, |res, (i, _)| res | (1 << i))
+}
+
+/// The inverse of `compress`.
+fn expand(set: u64, squares: u64) -> u64 {
+    bits(squares).enumerate().filter(|&(i, _)| set & (1 << i) != 0).fold(0, |res, (_, sq)| res | (1 << sq))
+}
+
+/// Index of a position with White to move in the table of its material.
+fn index(material: &Material, white: u64, black: u64, kings: u64) -> usize {
+    let (white_men, black_men) = (white & !kings, black & !kings);
+    let sets = [
+        (white_men, WHITE_MEN),
+        (black_men, BLACK_MEN),
+        (white & kings, SQUARES & !(white_men | black_men)),
+        (black & kings, SQUARES & !(white_men | black_men | white))
+    ];
+    material.groups().iter().zip(sets).fold(0, |idx, (&(count, squares), (set, on))| {
+        // Rank of the set among all sets of `count` squares, in colexicographic order.
+        let rank = bits(compress(set, on)).enumerate().map(|(i, sq)| binomial(sq, i+1)).sum::<usize>();
+        idx * binomial(squares, count as usize) + rank
+    })
+}
+
+/// The white pieces, black pieces and kings at `idx`, or None if a white and a black man share a square.
+fn position_at(material: &Material, mut idx: usize) -> Option<(u64, u64, u64)> {
+    let mut sets = [0u64; 4];
+    for (set, &(count, squares)) in sets.iter_mut().zip(material.groups().iter()).rev() {
+        let size = binomial(squares, count as usize);
+        let mut rank = idx % size;
+        idx /= size;
+        for i in (1..=count as usize).rev() {
+            let mut sq = i-1;
+            while binomial(sq+1, i) <= rank {
+                sq += 1;
+            }
+            rank -= binomial(sq, i);
+            *set |= 1 << sq;
+        }
+    }
+    let [white_men, black_men, white_kings, black_kings] = sets;
+    let (white_men, black_men) = (expand(white_men, WHITE_MEN), expand(black_men, BLACK_MEN));
+    if white_men & black_men != 0 {
+        return None;
+    }
+    let white_kings = expand(white_kings, SQUARES & !(white_men | black_men));
+    let black_kings = expand(black_kings, SQUARES & !(white_men | black_men | white_kings));
+    Some((white_men | white_kings, black_men | black_kings, white_kings | black_kings))
+}
+
+/// The values of the positions of one material, four to a byte, and their distances in a byte each if they are kept.
+struct Table {
+    values: Vec<u8>,
+    distances: Option<Vec<u8>>
+}
+
+impl Table {
+    fn from_entries(entries: &[u16]) -> Self {
+        let mut values = vec![0; entries.len().div_ceil(4)];
+        for (idx, &entry) in entries.iter().enumerate() {
+            let value = match TablebaseValue::from_entry(entry) {
+                TablebaseValue::Draw => 0,
+                TablebaseValue::Loss(_) => 1,
+                TablebaseValue::Win(_) => 2
+            };
+            values[idx/4] |= value << (idx%4 * 2);
+        }
+        let distances = entries.iter().map(|&entry| entry.saturating_sub(1) as u8).collect();
+        Self { values, distances: Some(distances) }
+    }
+
+    fn value(&self, idx: usize) -> TablebaseValue {
+        let distance = self.distances.as_ref().map(|distances| distances[idx] as u32);
+        match self.values[idx/4] >> (idx%4 * 2) & 3 {
+            1 => TablebaseValue::Loss(distance),
+            2 => TablebaseValue::Win(distance),
+            _ => TablebaseValue::Draw
+        }
+    }
+}
+
+/// Win, loss or draw with the distance to the end for every 8x8 position with few pieces.
+///
+/// Only positions with White to move are stored; the others are looked up with the colours flipped.
+/// Without the distances a tablebase takes a fifth of the space.
+pub struct Tablebase {
+    rules: RuleSet,
+    pieces: u32,
+    tables: HashMap<Material, Table>
+}
+
+const MAGIC: &[u8; 5] = b"CKTB3";
+
+/// Why `Tablebase::generate` failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GenerateError {
+    /// Tablebases are only generated for 8x8 boards.
+    UnsupportedRules(RuleSet),
+    /// More pieces than `MAX_PIECES`.
+    TooManyPieces(u32),
+    /// A position of `material` is further from the end of the game than an entry can hold.
+    DistanceTooLong(Material)
+}
+
+impl Display for GenerateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenerateError::UnsupportedRules(rules) => write!(f, "{} is not played on an 8x8 board", rules),
+            GenerateError::TooManyPieces(pieces) => write!(f, "{} pieces is more than the {} a tablebase can have", pieces, MAX_PIECES),
+            GenerateError::DistanceTooLong(material) => write!(f, "a position of {} is too far from the end of the game", material)
+        }
+    }
+}
+
+impl std::error::Error for GenerateError {}
+
+impl Tablebase {
+    /// Solves every position with up to `pieces` pieces under `rules` by retrograde analysis,
+    /// calling `progress` after each material.
+    pub fn generate(rules: RuleSet, pieces: u32, mut progress: impl FnMut(&Material, &[u16])) -> Result<Self, GenerateError> {
+        if rules.size() != 8 {
+            return Err(GenerateError::UnsupportedRules(rules));
+        }
+        if pieces > MAX_PIECES {
+            return Err(GenerateError::TooManyPieces(pieces));
+        }
+        let mut res = Self { rules, pieces, tables: HashMap::new() };
+        for material in Material::all(pieces) {
+            if res.tables.contains_key(&material) {
+                continue;
+            }
+            let solved = res.solve(material)?;
+            for (material, entries) in solved {
+                progress(&material, &entries);
+                res.tables.insert(material, Table::from_entries(&entries));
+            }
+        }
+        Ok(res)
+    }
+
+    pub fn rules(&self) -> RuleSet {
+        self.rules
+    }
+
+    /// Most pieces of the positions in the tablebase.
+    pub fn pieces(&self) -> u32 {
+        self.pieces
+    }
+
+    pub fn has_distances(&self) -> bool {
+        self.tables.values().all(|table| table.distances.is_some())
+    }
+
+    /// Keeps only whether positions are won, lost or drawn.
+    pub fn drop_distances(&mut self) {
+        for table in self.tables.values_mut() {
+            table.distances = None;
+        }
+    }
+
+    /// The value of `board` with perfect play, if it has few enough pieces and no capture in progress.
+    pub fn probe(&self, board: &Board) -> Option<TablebaseValue> {
+        if board.rules != self.rules || board.captured != 0 || (board.white | board.black).count_ones() > self.pieces {
+            return None;
+        }
+        if board.white == 0 || board.black == 0 {
+            return None;
+        }
+        // A capture that has to be continued by one piece is not a position of its own.
+        if !board.must_jump.is_empty() {
+            let mut fresh = board.clone();
+            fresh.find_forced_jumps();
+            if fresh.must_jump != board.must_jump {
+                return None;
+            }
+        }
+        self.value(board.turn, board.white, board.black, board.kings)
+    }
+
+    /// A move keeping the value of `board` with perfect play: the fastest win, the slowest loss or a drawing move.
+    /// Without the distances it is any winning move, which need not bring the win closer.
+    pub fn best_move(&self, board: &Board) -> Option<(Move, TablebaseValue)> {
+        let value = self.probe(board)?;
+        let best = board.legal_moves().into_iter().find(|mv| self.keeps_value(board, mv, value))?;
+        Some((best, value))
+    }
+
+    /// Whether `mv` keeps `value`, the value of `board`, as `best_move` would.
+    pub fn keeps_value(&self, board: &Board, mv: &Move, value: TablebaseValue) -> bool {
+        let mut child = board.clone();
+        child.apply_unchecked(mv);
+        let child_value = if child.legal_moves().is_empty() { Some(TablebaseValue::Loss(Some(0))) } else { self.probe(&child) };
+        match (value, child_value) {
+            (TablebaseValue::Win(Some(d)), Some(TablebaseValue::Loss(Some(c)))) => c+1 == d,
+            (TablebaseValue::Loss(Some(d)), Some(TablebaseValue::Win(Some(c)))) => c+1 == d,
+            (TablebaseValue::Win(None), Some(TablebaseValue::Loss(_))) => true,
+            (TablebaseValue::Loss(None), _) => true,
+            (TablebaseValue::Draw, child) => child == Some(TablebaseValue::Draw),
+            _ => false
+        }
+    }
+
+    /// The value of a position given by its pieces, flipping it if Black is to move.
+    fn value(&self, turn: Color, white: u64, black: u64, kings: u64) -> Option<TablebaseValue> {
+        let geo = Geometry::of(RuleSet::American);
+        let (white, black, kings) = match turn {
+            Color::White => (white, black, kings),
+            Color::Black => (geo.rotate(black), geo.rotate(white), geo.rotate(kings))
+        };
+        if (white & !kings & geo.promotion_row(Color::White)) | (black & !kings & geo.promotion_row(Color::Black)) != 0 {
+            return None;
+        }
+        let material = Material {
+            white_men: (white & !kings).count_ones(),
+            white_kings: (white & kings).count_ones(),
+            black_men: (black & !kings).count_ones(),
+            black_kings: (black & kings).count_ones()
+        };
+        self.tables.get(&material).map(|table| table.value(index(&material, white, black, kings)))
+    }
+
+    /// Solves `material` together with its colour-flipped version, which its quiet moves lead to.
+    fn solve(&self, material: Material) -> Result<Vec<(Material, Vec<u16>)>, GenerateError> {
+        let mut materials = vec![material];
+        if material.flipped() != material {
+            materials.push(material.flipped());
+        }
+        let offsets = [0, material.table_len()];
+        let total = materials.iter().map(|m| m.table_len()).sum::<usize>();
+        // Finds the id of a position of this group with White to move.
+        let id_of = |board: &Board| {
+            let m = Material::of(board);
+            let which = materials.iter().position(|&other| other == m).unwrap();
+            offsets[which] + index(&m, board.white, board.black, board.kings)
+        };
+        let board_at = |id: usize| {
+            let which = usize::from(id >= offsets[1] && materials.len() > 1);
+            let (white, black, kings) = position_at(&materials[which], id - offsets[which])?;
+            let mut board = Board { rules: self.rules, white, black, kings, turn: Color::White, ..Board::default() };
+            board.recompute_hash();
+            board.find_forced_jumps();
+            Some(board)
+        };
+
+        let mut entries = vec![0u16; total];
+        let mut resolved = vec![false; total];
+        // Moves to positions of the group not known to be wins for the opponent yet.
+        let mut remaining = vec![0u8; total];
+        // Longest win of the opponent among the known replies.
+        let mut longest = vec![0u16; total];
+        // Whether every reply may still turn out to be a win for the opponent.
+        let mut can_lose = vec![true; total];
+        // Positions waiting to be resolved at each distance.
+        let mut buckets = vec![];
+        let push = |buckets: &mut Vec<Vec<usize>>, id, distance: usize| {
+            if distance > u8::MAX as usize {
+                return Err(GenerateError::DistanceTooLong(material));
+            }
+            if buckets.len() <= distance {
+                buckets.resize(distance+1, vec![]);
+            }
+            buckets[distance].push(id);
+            Ok(())
+        };
+
+        for id in 0..total {
+            let Some(board) = board_at(id) else {
+                resolved[id] = true;
+                continue;
+            };
+            let moves = board.legal_moves();
+            let mut fastest_win = None;
+            for mv in &moves {
+                let mut child = board.clone();
+                child.apply_unchecked(mv);
+                if Material::of(&child) == Material::of(&board) {
+                    remaining[id] += 1;
+                    continue;
+                }
+                let value = if child.legal_moves().is_empty() {
+                    TablebaseValue::Loss(Some(0))
+                }
+                else {
+                    self.value(child.turn, child.white, child.black, child.kings).unwrap()
+                };
+                // The tables solved so far all have their distances.
+                match value {
+                    TablebaseValue::Loss(d) => fastest_win = Some(fastest_win.map_or(d.unwrap()+1, |w: u32| w.min(d.unwrap()+1))),
+                    TablebaseValue::Win(d) => longest[id] = longest[id].max(d.unwrap() as u16),
+                    TablebaseValue::Draw => can_lose[id] = false
+                }
+            }
+            if moves.is_empty() {
+                push(&mut buckets, id, 0)?;
+            }
+            else if let Some(d) = fastest_win {
+                can_lose[id] = false;
+                push(&mut buckets, id, d as usize)?;
+            }
+            else if remaining[id] == 0 && can_lose[id] {
+                push(&mut buckets, id, longest[id] as usize + 1)?;
+            }
+        }
+
+        // Resolving a position may fill buckets beyond the last one.
+        let mut distance = 0;
+        while distance < buckets.len() {
+            let ids = std::mem::take(&mut buckets[distance]);
+            for id in ids {
+                if resolved[id] {
+                    continue;
+                }
+                resolved[id] = true;
+                entries[id] = distance as u16 + 1;
+                let board = board_at(id).unwrap();
+                for pred in self.predecessors(&board) {
+                    let pred = id_of(&pred);
+                    if resolved[pred] {
+                        continue;
+                    }
+                    if distance%2 == 0 {
+                        can_lose[pred] = false;
+                        push(&mut buckets, pred, distance+1)?;
+                    }
+                    else {
+                        remaining[pred] -= 1;
+                        longest[pred] = longest[pred].max(distance as u16);
+                        if remaining[pred] == 0 && can_lose[pred] {
+                            push(&mut buckets, pred, longest[pred] as usize + 1)?;
+                        }
+                    }
+                }
+            }
+            distance += 1;
+        }
+
+        let mut tables = vec![];
+        let mut rest = entries;
+        for m in materials.iter().rev() {
+            let table = rest.split_off(rest.len() - m.table_len());
+            tables.push((*m, table));
+        }
+        tables.reverse();
+        Ok(tables)
+    }
+
+    /// Positions with White to move that reach `board` (White to move) by a quiet move which doesn't crown,
+    /// with the colours flipped so that it is White who moves into it. Only their pieces are set.
+    fn predecessors(&self, board: &Board) -> Vec<Board> {
+        let geo = board.geo();
+        let empty = board.empty();
+        let mut res = vec![];
+        for to in bits(board.black) {
+            let king = board.kings & (1 << to) != 0;
+            // Black men move down, so they came from above.
+            let dirs: &[MoveDir] = if king { &DIRS } else { &[MoveDir::TopLeft, MoveDir::TopRight] };
+            for &dir in dirs {
+                let mut from = to;
+                while let Some(next) = geo.step(from, dir).filter(|&sq| empty & (1 << sq) != 0) {
+                    from = next;
+                    let pred = Board {
+                        rules: self.rules,
+                        white: board.white,
+                        black: board.black ^ (1 << to) ^ (1 << from),
+                        kings: if king { board.kings ^ (1 << to) ^ (1 << from) } else { board.kings },
+                        turn: Color::Black,
+                        ..Board::default()
+                    };
+                    // Captures are compulsory, so the move was legal if Black had none.
+                    if pred.jumpers_of(Color::Black) == 0 {
+                        res.push(Board {
+                            rules: self.rules,
+                            white: geo.rotate(pred.black),
+                            black: geo.rotate(pred.white),
+                            kings: geo.rotate(pred.kings),
+                            ..Board::default()
+                        });
+                    }
+                    if !(king && self.rules.flying_kings()) {
+                        break;
+                    }
+                }
+            }
+        }
+        res
+    }
+
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        let name = self.rules.to_string();
+        writer.write_all(&[name.len() as u8])?;
+        writer.write_all(name.as_bytes())?;
+        writer.write_all(&[self.pieces as u8, self.has_distances() as u8])?;
+        for material in Material::all(self.pieces) {
+            let table = &self.tables[&material];
+            writer.write_all(&table.values)?;
+            if let Some(distances) = &table.distances {
+                writer.write_all(distances)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read_from(reader: &mut impl Read) -> io::Result<Self> {
+        let invalid = |what: &str| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid tablebase: {}", what));
+        let mut magic = [0; 5];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("unknown format"));
+        }
+        let mut len = [0; 1];
+        reader.read_exact(&mut len)?;
+        let mut name = vec![0; len[0] as usize];
+        reader.read_exact(&mut name)?;
+        let rules = String::from_utf8(name).ok().and_then(|name| name.parse::<RuleSet>().ok()).ok_or_else(|| invalid("unknown rules"))?;
+        let mut header = [0; 2];
+        reader.read_exact(&mut header)?;
+        let [pieces, has_distances] = header;
+        let pieces = pieces as u32;
+        if rules.size() != 8 || pieces > MAX_PIECES || has_distances > 1 {
+            return Err(invalid("unsupported rules or piece count"));
+        }
+        let mut tables = HashMap::new();
+        for material in Material::all(pieces) {
+            let values = Self::read_bytes(reader, material.table_len().div_ceil(4))?;
+            let distances = if has_distances == 1 { Some(Self::read_bytes(reader, material.table_len())?) } else { None };
+            tables.insert(material, Table { values, distances });
+        }
+        Ok(Self { rules, pieces, tables })
+    }
+
+    /// Reads `len` bytes.
+    fn read_bytes(reader: &mut impl Read, len: usize) -> io::Result<Vec<u8>> {
+        let mut bytes = vec![0; len];
+        reader.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = io::BufWriter::new(std::fs::File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read_from(&mut io::BufReader::new(std::fs::File::open(path)?))
+    }
+}
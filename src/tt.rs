@@ -0,0 +1,174 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// How a stored score relates to the true score of the position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    /// The search failed high: the true score is at least the stored one.
+    Lower,
+    /// The search failed low: the true score is at most the stored one.
+    Upper
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableEntry {
+    pub score: i64,
+    /// Remaining depth the score was searched to.
+    pub depth: u8,
+    pub bound: Bound,
+    /// Index of the best move in `legal_moves` order, if one was found.
+    pub best_move: Option<u16>
+}
+
+/// Lookup counts since the last `new_search`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TableStats {
+    pub probes: u64,
+    pub hits: u64,
+    pub stores: u64,
+    /// Stores that replaced an entry of another position.
+    pub overwrites: u64
+}
+
+/// One entry in two words; the key is stored xor-ed with the data so a torn write reads as a miss.
+#[derive(Default)]
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64
+}
+
+/// Fixed-size transposition table that threads can share without locking.
+///
+/// Each position has one slot. A store replaces the entry of another position
+/// when it was searched at most as deep or in an earlier search.
+pub struct TranspositionTable {
+    slots: Vec<Slot>,
+    age: u8,
+    probes: AtomicU64,
+    hits: AtomicU64,
+    stores: AtomicU64,
+    overwrites: AtomicU64
+}
+
+const AGE_BITS: u32 = 6;
+const NO_MOVE: u64 = 0xFFFF;
+
+// Data layout: score in bits 0-31, depth in 32-39, bound in 40-41 (0 for an empty slot), age in 42-47, best move in 48-63.
+fn pack(entry: TableEntry, age: u8) -> u64 {
+    let bound = match entry.bound {
+        Bound::Exact => 1,
+        Bound::Lower => 2,
+        Bound::Upper => 3
+    };
+    let score = entry.score.clamp(i32::MIN as i64, i32::MAX as i64) as i32 as u32 as u64;
+    let best_move = entry.best_move.map_or(NO_MOVE, |i| (i as u64).min(NO_MOVE-1));
+    score | (entry.depth as u64) << 32 | bound << 40 | (age as u64) << 42 | best_move << 48
+}
+
+/// The entry and its age, or None for an empty slot.
+fn unpack(data: u64) -> Option<(TableEntry, u8)> {
+    let bound = match (data >> 40) & 3 {
+        1 => Bound::Exact,
+        2 => Bound::Lower,
+        3 => Bound::Upper,
+        _ => return None
+    };
+    let best_move = data >> 48;
+    let entry = TableEntry {
+        score: data as u32 as i32 as i64,
+        depth: (data >> 32) as u8,
+        bound,
+        best_move: (best_move != NO_MOVE).then_some(best_move as u16)
+    };
+    Some((entry, (data >> 42) as u8 & ((1 << AGE_BITS) - 1)))
+}
+
+impl TranspositionTable {
+    /// A table of `entries` slots, rounded up to a power of two.
+    pub fn new(entries: usize) -> Self {
+        Self {
+            slots: (0..entries.max(1).next_power_of_two()).map(|_| Slot::default()).collect(),
+            age: 0,
+            probes: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            stores: AtomicU64::new(0),
+            overwrites: AtomicU64::new(0)
+        }
+    }
+
+    /// A table of about `megabytes` MiB.
+    pub fn with_size_mb(megabytes: usize) -> Self {
+        let entries = ((megabytes << 20) / std::mem::size_of::<Slot>()).max(1);
+        Self::new(1 << entries.ilog2())
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    fn slot(&self, hash: u64) -> &Slot {
+        &self.slots[hash as usize & (self.slots.len() - 1)]
+    }
+
+    /// Starts a new search: entries stored so far lose their priority, and the statistics are reset.
+    pub fn new_search(&mut self) {
+        self.age = (self.age + 1) & ((1 << AGE_BITS) - 1);
+        for counter in [&mut self.probes, &mut self.hits, &mut self.stores, &mut self.overwrites] {
+            *counter.get_mut() = 0;
+        }
+    }
+
+    /// Empties every slot.
+    pub fn clear(&mut self) {
+        for slot in &mut self.slots {
+            *slot.key.get_mut() = 0;
+            *slot.data.get_mut() = 0;
+        }
+    }
+
+    pub fn probe(&self, hash: u64) -> Option<TableEntry> {
+        self.probes.fetch_add(1, Ordering::Relaxed);
+        let slot = self.slot(hash);
+        let data = slot.data.load(Ordering::Relaxed);
+        if slot.key.load(Ordering::Relaxed) ^ data != hash {
+            return None;
+        }
+        let (entry, _) = unpack(data)?;
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some(entry)
+    }
+
+    pub fn store(&self, hash: u64, mut entry: TableEntry) {
+        let slot = self.slot(hash);
+        let old_data = slot.data.load(Ordering::Relaxed);
+        let old_key = slot.key.load(Ordering::Relaxed) ^ old_data;
+        if let Some((old, old_age)) = unpack(old_data) {
+            if old_key == hash {
+                entry.best_move = entry.best_move.or(old.best_move);
+            }
+            else if old_age == self.age && old.depth > entry.depth {
+                return;
+            }
+            else {
+                self.overwrites.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.stores.fetch_add(1, Ordering::Relaxed);
+        let data = pack(entry, self.age);
+        slot.key.store(hash ^ data, Ordering::Relaxed);
+        slot.data.store(data, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> TableStats {
+        TableStats {
+            probes: self.probes.load(Ordering::Relaxed),
+            hits: self.hits.load(Ordering::Relaxed),
+            stores: self.stores.load(Ordering::Relaxed),
+            overwrites: self.overwrites.load(Ordering::Relaxed)
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use std::time::Duration;
+
+use checkers::*;
+
+fn engine() -> AI {
+    let mut ai = AI::new();
+    ai.set_max_depth(8);
+    ai
+}
+
+#[test]
+fn no_legal_move_gives_no_move() {
+    let board = Board::from_fen("B:W5,6,10:B1", RuleSet::American).unwrap();
+    assert!(board.legal_moves().is_empty());
+    assert!(engine().compute_move(&board, Duration::from_millis(50)).is_none());
+}
+
+/// Wins stored in the table by earlier searches must not make a slower win look as quick.
+#[test]
+fn the_quickest_win_is_played() {
+    // 20-16 leaves the man on 12 without a move.
+    let board = Board::from_fen("W:W19,20,K32:B12", RuleSet::American).unwrap();
+    assert!(board.legal_moves().len() > 1);
+    let mut ai = engine();
+    for _ in 0..3 {
+        let mv = ai.compute_move(&board, Duration::from_millis(100)).unwrap();
+        assert_eq!(board.numeric_move(&mv), "20-16");
+    }
+}
+
+/// A single thread is the default, so fresh engines agree on every move.
+#[test]
+fn the_default_search_is_deterministic() {
+    let board = Board::from_fen("B:W18,21,22,23,24,25,26,27,28,29,30,31,32:B1,2,3,4,5,6,7,8,9,10,11,12", RuleSet::American).unwrap();
+    let moves = (0..3).map(|_| {
+        let mut ai = engine();
+        board.numeric_move(&ai.compute_move(&board, Duration::from_secs(10)).unwrap())
+    }).collect::<Vec<_>>();
+    assert!(moves.windows(2).all(|pair| pair[0] == pair[1]), "{:?}", moves);
+}
@@ -0,0 +1,88 @@
+use checkers::*;
+use rand::thread_rng;
+
+/// A game from the start position playing the legal moves at `choices`.
+fn game(choices: &[usize], result: PdnResult) -> PdnGame {
+    let mut game = PdnGame::new(Board::new());
+    let mut board = game.start.clone();
+    for &choice in choices {
+        let mv = board.legal_moves()[choice].clone();
+        board.apply_unchecked(&mv);
+        game.moves.push(mv);
+    }
+    game.result = result;
+    game
+}
+
+fn hash_after(choices: &[usize]) -> u64 {
+    game(choices, PdnResult::Draw).board().hash
+}
+
+#[test]
+fn results_are_counted_for_the_side_that_moved() {
+    let first = Board::new().turn;
+    let mut book = OpeningBook::new(RuleSet::Standard);
+    assert!(book.add_game(&game(&[0, 0, 0], PdnResult::Win(first)), 2));
+    assert!(book.add_game(&game(&[0, 1], PdnResult::Draw), 2));
+    assert!(book.add_game(&game(&[1, 0], PdnResult::Win(-first)), 2));
+    assert!(!book.add_game(&game(&[2], PdnResult::Unfinished), 2));
+    let mut other_rules = game(&[], PdnResult::Draw);
+    other_rules.start = Board::with_rules(RuleSet::Russian);
+    assert!(!book.add_game(&other_rules, 2));
+
+    assert_eq!(book.len(), 5);
+    assert_eq!(book.get(hash_after(&[0])), Some(BookEntry { wins: 1, draws: 1, losses: 0 }));
+    assert_eq!(book.get(hash_after(&[0, 0])), Some(BookEntry { wins: 0, draws: 0, losses: 1 }));
+    assert_eq!(book.get(hash_after(&[1, 0])), Some(BookEntry { wins: 1, draws: 0, losses: 0 }));
+    assert_eq!(book.get(hash_after(&[0, 0, 0])), None);
+
+    book.prune(2);
+    assert_eq!(book.len(), 1);
+}
+
+#[test]
+fn book_moves_are_weighted_by_their_results() {
+    let first = Board::new().turn;
+    let mut book = OpeningBook::new(RuleSet::Standard);
+    book.add_game(&game(&[0], PdnResult::Win(-first)), 1);
+    book.add_game(&game(&[1], PdnResult::Draw), 1);
+
+    let board = Board::new();
+    let moves = book.moves(&board);
+    assert_eq!(moves.len(), 2);
+    for _ in 0..20 {
+        assert!(book.pick_move(&board, &mut thread_rng()).unwrap() == board.legal_moves()[1]);
+    }
+
+    // Out of book, with only losing moves or under other rules, the search decides.
+    let mut after = board.clone();
+    after.apply_unchecked(&board.legal_moves()[1]);
+    assert!(book.pick_move(&after, &mut thread_rng()).is_none());
+    let mut losing = OpeningBook::new(RuleSet::Standard);
+    losing.add_game(&game(&[0], PdnResult::Win(-first)), 1);
+    assert!(losing.pick_move(&board, &mut thread_rng()).is_none());
+    assert!(book.moves(&Board::with_rules(RuleSet::Russian)).is_empty());
+}
+
+#[test]
+fn book_round_trips() {
+    let first = Board::new().turn;
+    let mut book = OpeningBook::new(RuleSet::Standard);
+    book.add_game(&game(&[0, 2, 1, 3], PdnResult::Win(first)), 10);
+    book.add_game(&game(&[3, 1, 1], PdnResult::Draw), 10);
+    let mut bytes = vec![];
+    book.write_to(&mut bytes).unwrap();
+    let read = OpeningBook::read_from(&mut bytes.as_slice()).unwrap();
+    assert!(read.rules() == RuleSet::Standard);
+    assert_eq!(read.len(), book.len());
+    for choices in [&[0][..], &[0, 2, 1, 3], &[3, 1], &[3, 1, 1]] {
+        let hash = hash_after(choices);
+        assert_eq!(read.get(hash), book.get(hash));
+    }
+
+    let mut again = vec![];
+    read.write_to(&mut again).unwrap();
+    assert_eq!(again, bytes);
+    assert!(OpeningBook::read_from(&mut &bytes[..bytes.len()-1]).is_err());
+    assert!(OpeningBook::read_from(&mut &b"nonsense"[..]).is_err());
+}
@@ -0,0 +1,21 @@
+//! Helpers shared by the integration tests.
+
+use checkers::*;
+
+/// A deterministic game under `rules`: each position with the move played from it, picked as the legal move at
+/// `(ply*7 + seed*11 + 3) % count`, for `plies` plies or until the side to move has none.
+pub fn play(rules: RuleSet, plies: usize, seed: usize) -> Vec<(Board, Move)> {
+    let mut board = Board::with_rules(rules);
+    let mut res = vec![];
+    for ply in 0..plies {
+        let mut moves = board.legal_moves();
+        if moves.is_empty() {
+            break;
+        }
+        let mv = moves.swap_remove((ply*7 + seed*11 + 3) % moves.len());
+        let mut next = board.clone();
+        next.apply_unchecked(&mv);
+        res.push((std::mem::replace(&mut board, next), mv));
+    }
+    res
+}
@@ -0,0 +1,88 @@
+use checkers::*;
+
+mod common;
+
+fn after(board: &Board, mv: &Move) -> Board {
+    let mut board = board.clone();
+    board.apply_unchecked(mv);
+    board
+}
+
+/// Plays `mv` one landing square at a time.
+fn replay(board: &Board, mv: &Move) -> Board {
+    let mut board = board.clone();
+    let mut from = mv.from;
+    for &to in &mv.path {
+        assert!(board.make_move_to(from, to).is_some(), "{} to {}", from, to);
+        from = to;
+    }
+    board
+}
+
+#[test]
+fn quiet_move_is_found() {
+    let before = Board::with_rules(RuleSet::American);
+    let mv = before.parse_numeric_move("11-15").unwrap();
+    assert!(Board::diff_to_move(&before, &after(&before, &mv)) == Some(mv));
+}
+
+#[test]
+fn multi_jump_is_found_whole() {
+    let before = Board::from_fen("W:W22,K31:B18,11,26", RuleSet::Standard).unwrap();
+    let mv = before.parse_numeric_move("22x15x8").unwrap();
+    let found = Board::diff_to_move(&before, &after(&before, &mv)).unwrap();
+    assert_eq!(found.path, [cell(4, 4), cell(6, 6)]);
+    assert_eq!(replay(&before, &found).fen(), after(&before, &mv).fen());
+}
+
+#[test]
+fn side_to_move_of_after_is_ignored() {
+    let before = Board::with_rules(RuleSet::American);
+    let mv = before.parse_numeric_move("9-14").unwrap();
+    let mut next = after(&before, &mv);
+    next.turn = -next.turn;
+    next.recompute_hash();
+    assert!(before.played_move(&next) == Some(mv));
+}
+
+#[test]
+fn unrelated_boards_have_no_move() {
+    let before = Board::with_rules(RuleSet::American);
+    assert!(Board::diff_to_move(&before, &before).is_none());
+
+    let mut record = GameRecord::new(before.clone());
+    for mv in ["11-15", "23-19"] {
+        let mv = record.board().parse_numeric_move(mv).unwrap();
+        assert!(record.play(&mv));
+    }
+    assert!(Board::diff_to_move(&before, record.board()).is_none());
+    assert!(Board::diff_to_move(&before, &Board::with_rules(RuleSet::International)).is_none());
+}
+
+#[test]
+fn every_move_of_a_game_is_found() {
+    for rules in [RuleSet::American, RuleSet::Russian, RuleSet::International] {
+        for (board, mv) in common::play(rules, 200, 0) {
+            let next = after(&board, &mv);
+            let found = board.played_move(&next).unwrap();
+            assert_eq!(after(&board, &found).hash, next.hash);
+        }
+    }
+}
+
+#[test]
+fn flying_king_moves_replay_exactly() {
+    let before = Board::from_fen("W:WK29:B4", RuleSet::Russian).unwrap();
+    let mv = before.parse_numeric_move("29-18").unwrap();
+    let found = Board::diff_to_move(&before, &after(&before, &mv)).unwrap();
+    assert_eq!(replay(&before, &found).fen(), "B:WK18:B4");
+
+    for rules in [RuleSet::Russian, RuleSet::International] {
+        for (board, mv) in common::play(rules, 200, 0) {
+            let next = after(&board, &mv);
+            let replayed = replay(&board, &Board::diff_to_move(&board, &next).unwrap());
+            assert_eq!(replayed.fen(), next.fen());
+            assert_eq!(replayed.hash, next.hash);
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use checkers::*;
+
+#[test]
+fn start_positions_round_trip() {
+    for rules in RuleSet::ALL {
+        let board = Board::with_rules(rules);
+        let parsed = Board::from_fen(&board.fen(), rules).unwrap();
+        assert_eq!(parsed.to_string(), board.to_string());
+        assert_eq!(parsed.hash, board.hash);
+    }
+    assert_eq!(Board::new().fen(), "B:W21,22,23,24,25,26,27,28,29,30,31,32:B1,2,3,4,5,6,7,8,9,10,11,12");
+}
+
+#[test]
+fn ranges_kings_and_side_to_move() {
+    let board = Board::from_fen("W:W21,22,K30:B1-3.", RuleSet::American).unwrap();
+    assert!(board.turn == Color::White);
+    assert_eq!(board.fen(), "W:W21,22,K30:B1,2,3");
+    let king = board[board.numbered_square(30).unwrap()].piece.unwrap();
+    assert!(king.king && king.color == Color::White);
+    assert_eq!(board.pieces(Color::Black).count_ones(), 3);
+
+    let ranges = Board::from_fen("B:B1-12:W21-32", RuleSet::American).unwrap();
+    assert_eq!(ranges.hash, Board::with_rules(RuleSet::American).hash);
+}
+
+#[test]
+fn forced_jumps_are_found() {
+    let board = Board::from_fen("B:W14:B9,K10", RuleSet::American).unwrap();
+    let jumpers = board.must_jump.iter().map(|&cp| board.square_number(cp).unwrap()).collect::<Vec<_>>();
+    assert_eq!(jumpers, [9, 10]);
+}
+
+#[test]
+fn rejects_malformed_fen() {
+    assert_eq!(Board::from_fen("X:W1:B2", RuleSet::Standard).err(), Some(ParseError::InvalidValue { expected: "FEN", found: "X:W1:B2".into() }));
+    assert_eq!(Board::from_fen("W:W33:B2", RuleSet::Standard).err(), Some(ParseError::InvalidValue { expected: "square number", found: "33".into() }));
+    assert!(Board::from_fen("W:W5:B5", RuleSet::Standard).is_err());
+    assert!(Board::from_fen("W:W45:B5", RuleSet::International).is_ok());
+}
+
+#[test]
+fn pdn_keeps_set_up_positions() {
+    let start = Board::from_fen("W:W21,22,K30:B1,2,3", RuleSet::American).unwrap();
+    let mut game = PdnGame::new(start.clone());
+    let mut board = start;
+    for _ in 0..4 {
+        let mv = board.legal_moves()[0].clone();
+        board.apply_unchecked(&mv);
+        game.moves.push(mv);
+    }
+    let text = game.to_string();
+    assert!(text.contains("[FEN \"W:W21,22,K30:B1,2,3\"]"));
+    assert!(text.contains("\n1... "));
+    let parsed: PdnGame = text.parse().unwrap();
+    assert_eq!(parsed.start.fen(), game.start.fen());
+    assert!(parsed.moves == game.moves);
+    assert_eq!(parsed.to_string(), text);
+}
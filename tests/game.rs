@@ -0,0 +1,51 @@
+use checkers::{*, game::NO_PROGRESS_PLIES};
+
+/// A move to a position seen at most once before in `game`, after which the opponent can capture only if `offer_capture`.
+fn king_move(game: &Game, offer_capture: bool) -> Move {
+    let record = game.record();
+    let seen = (0..=record.ply()).filter_map(|ply| record.hash_at(ply)).collect::<Vec<_>>();
+    game.board().legal_moves().into_iter().find(|mv| {
+        let mut next = game.board().clone();
+        next.apply_unchecked(mv);
+        seen.iter().filter(|&&hash| hash == next.hash).count() < 2 && next.legal_moves().iter().any(Move::is_capture) == offer_capture
+    }).unwrap()
+}
+
+fn kings_only() -> Game {
+    Game::from_board(Board::from_fen("B:WK29,K32:BK1,K4", RuleSet::American).unwrap())
+}
+
+#[test]
+fn forty_moves_without_progress_are_a_draw() {
+    let mut game = kings_only();
+    for ply in 0..NO_PROGRESS_PLIES {
+        assert!(game.outcome().is_none(), "ply {}", ply);
+        let mv = king_move(&game, false);
+        assert!(game.play(&mv));
+    }
+    assert!(game.outcome() == Some(Outcome::Draw(DrawReason::NoProgress)));
+}
+
+#[test]
+fn a_capture_resets_the_count() {
+    let mut game = kings_only();
+    for _ in 0..NO_PROGRESS_PLIES-2 {
+        let mv = king_move(&game, false);
+        assert!(game.play(&mv));
+    }
+    let offer = king_move(&game, true);
+    assert!(game.play(&offer));
+    let capture = game.board().legal_moves().remove(0);
+    assert!(capture.is_capture());
+    assert!(game.play(&capture));
+    assert_eq!(game.record().ply(), NO_PROGRESS_PLIES);
+    assert!(game.outcome().is_none());
+
+    // The count starts again from the capture.
+    for ply in 0..NO_PROGRESS_PLIES {
+        assert!(game.outcome().is_none(), "ply {} after the capture", ply);
+        let mv = king_move(&game, false);
+        assert!(game.play(&mv));
+    }
+    assert!(game.outcome() == Some(Outcome::Draw(DrawReason::NoProgress)));
+}
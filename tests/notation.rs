@@ -0,0 +1,65 @@
+use checkers::*;
+
+fn invalid(expected: &'static str, found: &str) -> ParseError {
+    ParseError::InvalidValue { expected, found: found.to_string() }
+}
+
+#[test]
+fn numbers_round_trip() {
+    for n in 1..=32 {
+        let cp = CellPos::from_number(n).unwrap();
+        assert_eq!(cp.number(), Some(n));
+        assert_eq!(Board::new().square_number(cp), Some(n));
+    }
+    assert!(CellPos::from_number(0).is_none());
+    assert!(CellPos::from_number(33).is_none());
+    assert_eq!(CellPos::new(0, 1).number(), None);
+
+    let corners = [1, 4, 29, 32].map(|n| CellPos::from_number(n).unwrap().to_string());
+    assert_eq!(corners, ["B7", "H7", "A0", "G0"]);
+}
+
+#[test]
+fn black_men_start_on_one_to_twelve() {
+    let board = Board::new();
+    for n in 1..=32 {
+        let piece = board[CellPos::from_number(n).unwrap()].piece;
+        let color = piece.map(|piece| piece.color);
+        match n {
+            1..=12 => assert!(color == Some(Color::Black)),
+            21..=32 => assert!(color == Some(Color::White)),
+            _ => assert!(color.is_none())
+        }
+    }
+}
+
+#[test]
+fn numeric_moves_split_into_steps() {
+    let steps = |s: &str| PieceMove::parse_numeric(s).map(|steps| steps.iter().map(|st| st.to_string()).collect::<Vec<_>>());
+    assert_eq!(steps("9-14").unwrap(), ["B5 dr"]);
+    assert_eq!(steps("22x15x8").unwrap(), ["C2 tr", "E4 tr"]);
+    assert_eq!(steps("9-10").err(), Some(invalid("diagonal move", "9-10")));
+    assert_eq!(steps("9").err(), Some(invalid("move", "9")));
+    assert_eq!(steps("9-33").err(), Some(invalid("square number", "33")));
+}
+
+#[test]
+fn steps_play_the_numeric_move() {
+    let mut board = Board::from_fen("W:W22,K31:B18,11,26", RuleSet::Standard).unwrap();
+    let full = board.parse_numeric_move("22x15x8").unwrap();
+    for step in PieceMove::parse_numeric("22x15x8").unwrap() {
+        assert!(board.make_move(step).is_some());
+    }
+    assert_eq!(board.fen(), "B:W8,K31:B26");
+    assert_eq!(full.captures.len(), 2);
+}
+
+#[test]
+fn squares_in_either_notation() {
+    let board = Board::new();
+    let numeric = board.parse_square("9", Notation::Numeric).unwrap();
+    let algebraic = board.parse_square("B5", Notation::Algebraic).unwrap();
+    assert!(numeric == algebraic);
+    assert!(board.parse_square("B5", Notation::Numeric).is_err());
+    assert_eq!("numeric".parse::<Notation>().unwrap().to_string(), "numeric");
+}
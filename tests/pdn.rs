@@ -0,0 +1,111 @@
+use checkers::*;
+
+mod common;
+
+/// Plays up to `plies` moves picked deterministically from the legal ones, stopping once the game is over.
+fn play(rules: RuleSet, plies: usize) -> Game {
+    let mut game = Game::with_rules(rules);
+    for (_, mv) in common::play(rules, plies, 0) {
+        if game.outcome().is_some() {
+            break;
+        }
+        assert!(game.play(&mv));
+    }
+    game
+}
+
+#[test]
+fn round_trip_every_rule_set() {
+    for rules in RuleSet::ALL {
+        let game = play(rules, 120);
+        let mut pdn = PdnGame::from_game(&game);
+        pdn.set_tag("Event", "Round \"trip\"");
+        let text = pdn.to_string();
+        let parsed: PdnGame = text.parse().unwrap();
+
+        assert!(parsed.start.rules() == rules);
+        assert!(parsed.moves == game.moves());
+        assert!(parsed.result == pdn.result);
+        assert_eq!(parsed.tag("Event"), Some("Round \"trip\""));
+        assert_eq!(parsed.board().hash, game.board().hash);
+        assert_eq!(parsed.to_string(), text);
+    }
+}
+
+#[test]
+fn steps_are_recorded_as_whole_moves() {
+    let mut game = Game::new();
+    let mut reference = play(RuleSet::Standard, 30);
+    for mv in reference.moves().to_vec() {
+        for step in mv.steps() {
+            assert!(game.play_step(step));
+        }
+    }
+    assert!(game.moves() == reference.moves());
+    assert!(reference.play(&game.board().legal_moves()[0]));
+}
+
+#[test]
+fn parses_american_notation() {
+    let text = r#"
+[Event "Test"]
+[Black "A"]
+[White "B"]
+[GameType "21"]
+
+1. 11-15 {the Old Fourteenth starts} 23-19 2. 8-11 22-17 (2... 27-24) 3.4-8 17-13
+4. 15-18 24-20 5. 9-14 $1 26-23! 6. 10-15 19x10 7. 6x15 *
+"#;
+    let game: PdnGame = text.parse().unwrap();
+    assert!(game.start.rules() == RuleSet::American);
+    assert!(game.result == PdnResult::Unfinished);
+    assert_eq!(game.tag("White"), Some("B"));
+    assert_eq!(game.moves.len(), 13);
+
+    let mut board = game.start.clone();
+    let notation = game.moves.iter().map(|mv| {
+        let s = board.numeric_move(mv);
+        board.apply_unchecked(mv);
+        s
+    }).collect::<Vec<_>>();
+    assert_eq!(notation[..4], ["11-15", "23-19", "8-11", "22-17"]);
+    assert_eq!(notation[11..], ["19x10", "6x15"]);
+}
+
+#[test]
+fn capture_may_give_only_its_end_square() {
+    let game = play(RuleSet::International, 200);
+    let mut board = game.start().clone();
+    for mv in game.moves() {
+        let full = board.numeric_move(mv);
+        let short = format!("{}x{}", board.square_number(mv.from).unwrap(), board.square_number(mv.to()).unwrap());
+        assert!(board.parse_numeric_move(&full).unwrap() == *mv);
+        if mv.is_capture() {
+            match board.parse_numeric_move(&short) {
+                Ok(parsed) => assert!(parsed == *mv),
+                Err(err) => assert_eq!(err, ParseError::InvalidValue { expected: "legal move", found: short })
+            }
+        }
+        board.apply_unchecked(mv);
+    }
+}
+
+#[test]
+fn several_games_and_results() {
+    let text = "[Result \"1-0\"]\n1. 11-15 1-0\n\n[GameType \"20\"]\n1. 32-28 19-23 2. 28x19 14x23 1/2-1/2\n[Event \"unfinished\"]\n1. 9-13";
+    let games = pdn::parse_games(text).unwrap();
+    assert_eq!(games.len(), 3);
+    assert!(games[0].result == PdnResult::Win(Color::Black));
+    assert!(games[1].start.rules() == RuleSet::International);
+    assert!(games[1].result == PdnResult::Draw);
+    assert_eq!(games[1].moves.len(), 4);
+    assert!(games[2].result == PdnResult::Unfinished);
+    assert_eq!(games[2].tag("Event"), Some("unfinished"));
+}
+
+#[test]
+fn rejects_illegal_moves() {
+    assert_eq!("1. 11-17 *".parse::<PdnGame>().err(), Some(ParseError::InvalidValue { expected: "legal move", found: "11-17".into() }));
+    assert_eq!("1. 11-15 {no end".parse::<PdnGame>().err(), Some(ParseError::MissingInput { expected: "end of comment" }));
+    assert!("[GameType \"99\"] *".parse::<PdnGame>().is_err());
+}
@@ -0,0 +1,66 @@
+use checkers::*;
+
+/// Published perft numbers for the American checkers starting position.
+const AMERICAN: [u64; 9] = [1, 7, 49, 302, 1469, 7361, 36768, 179740, 845931];
+
+#[test]
+fn american_start_position() {
+    let mut board = Board::with_rules(RuleSet::American);
+    for (depth, &nodes) in AMERICAN.iter().enumerate() {
+        assert_eq!(perft::perft(&mut board, depth as u32), nodes, "depth {}", depth);
+    }
+    assert_eq!(board.hash, Board::with_rules(RuleSet::American).hash);
+}
+
+#[test]
+fn parallel_matches_serial() {
+    let board = Board::with_rules(RuleSet::American);
+    for threads in [1, 3, 16] {
+        assert_eq!(perft::perft_parallel(&board, 7, threads), AMERICAN[7]);
+    }
+    let divide = perft::divide_parallel(&board, 6, 4);
+    assert_eq!(divide.len(), 7);
+    assert_eq!(divide.iter().map(|(_, nodes)| nodes).sum::<u64>(), AMERICAN[6]);
+    let numeric = divide.iter().map(|(mv, _)| board.numeric_move(mv)).collect::<Vec<_>>();
+    for mv in ["9-13", "9-14", "10-14", "10-15", "11-15", "11-16", "12-16"] {
+        assert!(numeric.contains(&mv.to_string()), "{}", mv);
+    }
+}
+
+/// Published perft numbers for the international draughts starting position.
+const INTERNATIONAL: [u64; 8] = [1, 9, 81, 658, 4265, 27117, 167140, 1049442];
+
+#[test]
+fn international_start_position() {
+    let mut board = Board::with_rules(RuleSet::International);
+    for (depth, &nodes) in INTERNATIONAL.iter().enumerate() {
+        assert_eq!(perft::perft(&mut board, depth as u32), nodes, "depth {}", depth);
+    }
+    assert_eq!(board.hash, Board::with_rules(RuleSet::International).hash);
+}
+
+/// Counts leaves by playing each move step by step with `make_move`, the way engines send them.
+fn perft_steps(board: &mut Board, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let mut nodes = 0;
+    for mv in board.legal_moves() {
+        let mut child = board.clone();
+        for step in mv.steps() {
+            assert!(child.make_move(step).is_some());
+        }
+        nodes += perft_steps(&mut child, depth-1);
+    }
+    nodes
+}
+
+#[test]
+fn step_moves_agree() {
+    let mut board = Board::with_rules(RuleSet::American);
+    assert_eq!(perft_steps(&mut board, 6), AMERICAN[6]);
+
+    // Kings and multi-jumps in the middle game.
+    let mut board = Board::from_fen("B:W18,19,21,23,24,26,29,30,31,32,K2:B1,3,4,6,7,9,10,11,12,K27", RuleSet::American).unwrap();
+    assert_eq!(perft_steps(&mut board, 5), perft::perft(&mut board, 5));
+}
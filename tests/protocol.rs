@@ -0,0 +1,73 @@
+use std::{io::Write, process::{Command, Stdio}};
+
+use checkers::*;
+
+/// Runs the engine on the raw `input` until it reaches the end of it, and returns the lines it answered.
+fn engine_output(input: &[u8]) -> Vec<String> {
+    let mut engine = Command::new(env!("CARGO_BIN_EXE_checkers"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    engine.stdin.take().unwrap().write_all(input).unwrap();
+    let output = engine.wait_with_output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap().lines().map(str::to_string).collect()
+}
+
+/// Runs the engine playing Black on `input`.
+fn engine_replies(input: &str) -> Vec<String> {
+    engine_output(format!("black\ntime 20\nthreads 1\n{}", input).as_bytes())
+}
+
+#[test]
+fn unreadable_positions_are_answered_with_an_error() {
+    let grid = Board::new().to_string();
+    let malformed = grid.replacen('.', "x", 1);
+    let replies = engine_replies(&format!("\n{}\nfen W:W33:B1\n\n{}\nrules russian\n\n{}\n", malformed, grid, Board::with_rules(RuleSet::International)));
+    assert_eq!(replies.len(), 4);
+    assert!(replies[0].starts_with("error "), "{}", replies[0]);
+    assert!(replies[1].starts_with("error "), "{}", replies[1]);
+    // The engine keeps answering positions after rejecting some.
+    assert!(replies[2].parse::<PieceMove>().is_ok(), "{}", replies[2]);
+    assert_eq!(replies[3], "error invalid board at line 1: wrong number of columns for the rules");
+}
+
+#[test]
+fn impossible_positions_are_answered_with_an_error() {
+    let replies = engine_replies("fen B:W1,22:B12\nfen B:W22:B12\n");
+    assert_eq!(replies.len(), 2);
+    assert!(replies[0].starts_with("error uncrowned man on "), "{}", replies[0]);
+    assert!(replies[1].parse::<PieceMove>().is_ok(), "{}", replies[1]);
+}
+
+#[test]
+fn captures_are_sent_one_step_at_a_time() {
+    let board = Board::from_fen("B:W9,18:B5", RuleSet::Standard).unwrap();
+    let first = engine_replies(&format!("{}\n{}", board.must_jump[0], board));
+    assert_eq!(first.len(), 1);
+    let mut partial = board.clone();
+    assert!(partial.make_move(first[0].parse().unwrap()).is_some());
+    assert!(partial.turn == Color::Black);
+
+    let replies = engine_replies(&format!("{}\n{}\n{}\n{}", board.must_jump[0], board, partial.must_jump[0], partial));
+    assert_eq!(replies[..1], first);
+    assert_eq!(replies.len(), 2);
+    assert!(partial.make_move(replies[1].parse().unwrap()).is_some());
+    assert_eq!(partial.fen(), "W:W:B23");
+}
+
+#[test]
+fn a_side_without_moves_resigns() {
+    assert_eq!(engine_replies("fen B:W5,6,10:B1\n"), ["resign"]);
+}
+
+#[test]
+fn unreadable_lines_are_answered_with_an_error() {
+    let replies = engine_output(b"purple\nblack\ntime 20\n\xff\xfe\nfen B:W22:B12\n");
+    assert_eq!(replies.len(), 3);
+    assert_eq!(replies[0], "error invalid color: \"purple\"");
+    assert!(replies[1].starts_with("error "), "{}", replies[1]);
+    assert!(replies[2].parse::<PieceMove>().is_ok(), "{}", replies[2]);
+}
@@ -0,0 +1,88 @@
+use checkers::*;
+
+fn opening(record: &mut GameRecord, moves: &[&str]) {
+    for mv in moves {
+        let mv = record.board().parse_numeric_move(mv).unwrap();
+        assert!(record.play(&mv));
+    }
+}
+
+#[test]
+fn undo_redo_and_goto() {
+    let start = Board::with_rules(RuleSet::American);
+    let mut record = GameRecord::new(start.clone());
+    opening(&mut record, &["11-15", "23-19", "8-11", "22-17"]);
+    let end = record.board().clone();
+    assert_eq!(record.ply(), 4);
+    assert_eq!(record.hash_at(4), Some(end.hash));
+
+    assert!(record.undo());
+    assert!(record.undo());
+    assert_eq!(record.ply(), 2);
+    assert_eq!(record.len(), 4);
+    assert_eq!(record.moves().len(), 2);
+    assert_eq!(record.board().hash, record.hash_at(2).unwrap());
+
+    assert!(record.redo());
+    assert!(record.goto(4));
+    assert_eq!(record.board().fen(), end.fen());
+    assert!(!record.redo());
+
+    assert!(record.goto(0));
+    assert_eq!(record.board().hash, start.hash);
+    assert!(!record.undo());
+    assert!(!record.goto(5));
+}
+
+#[test]
+fn playing_after_undo_replaces_the_rest() {
+    let mut record = GameRecord::new(Board::with_rules(RuleSet::American));
+    opening(&mut record, &["11-15", "23-19", "8-11"]);
+    record.goto(1);
+
+    // Playing the recorded move keeps the continuation.
+    opening(&mut record, &["23-19"]);
+    assert_eq!(record.len(), 3);
+
+    opening(&mut record, &["9-14"]);
+    assert_eq!(record.len(), 3);
+    record.undo();
+    assert_eq!(record.board().numeric_move(&record.all_moves()[2]), "9-14");
+}
+
+#[test]
+fn illegal_moves_are_refused() {
+    let mut record = GameRecord::new(Board::with_rules(RuleSet::American));
+    let illegal = Board::with_rules(RuleSet::American).parse_numeric_move("11-15").unwrap();
+    opening(&mut record, &["11-15"]);
+    assert!(!record.play(&illegal));
+    assert_eq!(record.len(), 1);
+}
+
+#[test]
+fn positions_follow_the_moves() {
+    let mut record = GameRecord::new(Board::with_rules(RuleSet::American));
+    opening(&mut record, &["11-15", "24-19", "15x24", "28x19"]);
+    record.goto(2);
+    let positions = record.positions().collect::<Vec<_>>();
+    assert_eq!(positions.len(), 5);
+    for (ply, board) in positions.iter().enumerate() {
+        assert_eq!(Some(board.hash), record.hash_at(ply));
+    }
+    assert_eq!(positions[4].pieces(Color::White).count_ones(), 11);
+    assert_eq!(positions[4].pieces(Color::Black).count_ones(), 11);
+}
+
+#[test]
+fn game_detects_repetition_from_its_record() {
+    let mut game = Game::from_board(Board::from_fen("B:WK29,K30:BK1,K2", RuleSet::American).unwrap());
+    let cycle = ["1-5", "29-25", "5-1", "25-29"];
+    for (i, mv) in cycle.iter().cycle().take(8).enumerate() {
+        assert!(game.outcome().is_none(), "ply {}", i);
+        let mv = game.board().parse_numeric_move(mv).unwrap();
+        assert!(game.play(&mv));
+    }
+    assert!(game.outcome() == Some(Outcome::Draw(DrawReason::Repetition)));
+    assert_eq!(game.record().ply(), 8);
+    assert_eq!(PdnGame::from_game(&game).moves.len(), 8);
+}
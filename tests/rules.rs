@@ -0,0 +1,92 @@
+use checkers::*;
+
+/// The legal moves of a FEN position in numeric notation, sorted.
+fn moves(fen: &str, rules: RuleSet) -> Vec<String> {
+    let board = Board::from_fen(fen, rules).unwrap();
+    let mut res = board.legal_moves().iter().map(|mv| board.numeric_move(mv)).collect::<Vec<_>>();
+    res.sort();
+    res
+}
+
+fn square(board: &Board, n: usize) -> CellPos {
+    board.numbered_square(n).unwrap()
+}
+
+#[test]
+fn russian_men_crowned_mid_capture_go_on_as_kings() {
+    // The man crowns on 2 after taking 6 and then takes 7 as a flying king, landing anywhere behind it.
+    assert_eq!(moves("W:W9:B6,7", RuleSet::Russian), ["9x2x11", "9x2x16", "9x2x20"]);
+    let mut board = Board::from_fen("W:W9:B6,16", RuleSet::Russian).unwrap();
+    let mv = board.parse_numeric_move("9x2x20").unwrap();
+    board.apply_unchecked(&mv);
+    assert!(board[square(&board, 20)].piece.is_some_and(|piece| piece.king));
+    assert_eq!(board.pieces(Color::Black), 0);
+}
+
+#[test]
+fn pool_men_are_only_crowned_where_the_move_ends() {
+    // Passing the far row mid-capture leaves a man, which goes on capturing backwards as one.
+    assert_eq!(moves("W:W9:B6,7", RuleSet::Pool), ["9x2x11"]);
+    let mut board = Board::from_fen("W:W9:B6,7", RuleSet::Pool).unwrap();
+    board.apply_unchecked(&board.legal_moves()[0]);
+    assert!(board[square(&board, 11)].piece.is_some_and(|piece| !piece.king));
+
+    let mut board = Board::from_fen("W:W9:B6", RuleSet::Pool).unwrap();
+    board.apply_unchecked(&board.legal_moves()[0]);
+    assert!(board[square(&board, 2)].piece.is_some_and(|piece| piece.king));
+}
+
+#[test]
+fn maximum_capture_is_compulsory() {
+    let fen = "W:W22,24:B11,17,19";
+    assert_eq!(moves(fen, RuleSet::Brazilian), ["24x15x8"]);
+    assert_eq!(moves(fen, RuleSet::Russian), ["22x13", "24x15x8"]);
+    assert_eq!(moves(fen, RuleSet::American), ["22x13", "24x15x8"]);
+}
+
+#[test]
+fn italian_men_cannot_capture_kings() {
+    assert_eq!(moves("W:W22:BK17", RuleSet::Italian), ["22-18"]);
+    assert_eq!(moves("W:W22:B17", RuleSet::Italian), ["22x13"]);
+    assert_eq!(moves("W:W22:BK17", RuleSet::American), ["22x13"]);
+}
+
+#[test]
+fn captured_pieces_stay_until_the_move_ends() {
+    // Two orders of taking the diamond reach the same squares, with different pieces already taken.
+    let start = Board::from_fen("W:W30:B17,18,25,26", RuleSet::Russian).unwrap();
+    assert_eq!(moves("W:W30:B17,18,25,26", RuleSet::Russian), ["30x21x14x23x30", "30x23x14x21x30"]);
+    let play = |path: &[usize]| {
+        let mut board = start.clone();
+        for step in path.windows(2) {
+            assert!(board.make_move_to(square(&board, step[0]), square(&board, step[1])).is_some());
+        }
+        board
+    };
+    let left = play(&[30, 21, 14]);
+    let right = play(&[30, 23, 14]);
+    assert_eq!(left.to_string(), right.to_string());
+    assert_eq!(left.pieces(Color::Black).count_ones(), 4);
+    assert!(left.turn == Color::White);
+    assert_ne!(left.hash, right.hash);
+    assert_eq!(left.legal_moves().iter().map(|mv| left.numeric_move(mv)).collect::<Vec<_>>(), ["14x23x30"]);
+    assert_eq!(right.legal_moves().iter().map(|mv| right.numeric_move(mv)).collect::<Vec<_>>(), ["14x21x30"]);
+
+    // A taken piece blocks, and is only removed once the move is complete.
+    let mut board = left.clone();
+    assert!(board.make_move_to(square(&board, 14), square(&board, 21)).is_none());
+    assert!(board.make_move_to(square(&board, 14), square(&board, 23)).is_some());
+    assert!(board.make_move_to(square(&board, 23), square(&board, 30)).is_some());
+    assert_eq!(board.pieces(Color::Black), 0);
+    assert!(board.turn == Color::Black);
+}
+
+#[test]
+fn rules_only_switch_on_the_same_board_size() {
+    let mut board = Board::from_fen("W:W22:BK17", RuleSet::Italian).unwrap();
+    assert!(board.set_rules(RuleSet::American).is_ok());
+    assert!(board.rules() == RuleSet::American);
+    assert_eq!(board.must_jump, [square(&board, 22)]);
+    assert!(board.set_rules(RuleSet::International).is_err());
+    assert!(board.rules() == RuleSet::American);
+}
@@ -0,0 +1,71 @@
+#![cfg(feature = "serde")]
+
+use checkers::*;
+
+fn round_trip<T: serde::Serialize + serde::de::DeserializeOwned>(value: &T) -> T {
+    serde_json::from_str(&serde_json::to_string(value).unwrap()).unwrap()
+}
+
+#[test]
+fn simple_types_are_strings() {
+    assert_eq!(serde_json::to_string(&Color::White).unwrap(), "\"white\"");
+    assert_eq!(serde_json::to_string(&cell(2, 2)).unwrap(), "\"C2\"");
+    assert_eq!(serde_json::to_string(&PieceMove {pos: cell(2, 2), dir: MoveDir::TopRight}).unwrap(), "\"C2 tr\"");
+    assert_eq!(serde_json::to_string(&RuleSet::Russian).unwrap(), "\"russian\"");
+    assert_eq!(serde_json::to_string(&Piece {king: true, color: Color::Black}).unwrap(), "\"B\"");
+    assert_eq!(serde_json::to_string(&Cell::default()).unwrap(), "null");
+
+    assert!(round_trip(&Color::Black) == Color::Black);
+    assert!(round_trip(&cell(9, 9)) == cell(9, 9));
+    assert!(round_trip(&Piece {king: false, color: Color::White}) == Piece {king: false, color: Color::White});
+    assert!(serde_json::from_str::<CellPos>("\"Z9\"").is_err());
+    assert!(serde_json::from_str::<Piece>("\"x\"").is_err());
+}
+
+#[test]
+fn boards_use_fen() {
+    let board = Board::with_rules(RuleSet::American);
+    let json = serde_json::to_string(&board).unwrap();
+    assert_eq!(json, format!("{{\"rules\":\"american\",\"fen\":\"{}\"}}", board.fen()));
+    let parsed: Board = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.hash, board.hash);
+}
+
+#[test]
+fn captures_in_progress_survive() {
+    // A Brazilian king mid-capture: captured pieces stay on the board until the move ends.
+    let mut board = Board::from_fen("W:WK32:B27,18,11", RuleSet::Brazilian).unwrap();
+    let mv = board.legal_moves().into_iter().max_by_key(|mv| mv.captures.len()).unwrap();
+    assert!(board.make_move(mv.steps()[0]).is_some());
+    assert!(!board.must_jump.is_empty());
+    assert!(serde_json::to_string(&board).unwrap().contains("\"captured\":[\""));
+
+    let mut parsed = round_trip(&board);
+    assert_eq!(parsed.to_string(), board.to_string());
+    assert!(parsed.must_jump == board.must_jump);
+    for step in &mv.steps()[1..] {
+        assert!(board.make_move(*step).is_some());
+        assert!(parsed.make_move(*step).is_some());
+    }
+    assert_eq!(parsed.fen(), board.fen());
+    assert_eq!(parsed.hash, board.hash);
+}
+
+#[test]
+fn must_jump_needs_a_piece_to_move() {
+    // A light square, an empty one, an opponent's piece and a square off the board.
+    for must_jump in ["A1", "C2", "B7", "Z9"] {
+        let json = format!("{{\"rules\":\"american\",\"fen\":\"W:W21,29:B1\",\"must_jump\":[\"{}\"]}}", must_jump);
+        assert!(serde_json::from_str::<Board>(&json).is_err(), "{}", must_jump);
+    }
+    let json = "{\"rules\":\"american\",\"fen\":\"B:W21,29:B1\",\"must_jump\":[\"B7\"]}";
+    assert!(serde_json::from_str::<Board>(json).unwrap().must_jump == [cell(7, 1)]);
+}
+
+#[test]
+fn moves_keep_their_squares() {
+    let board = Board::with_rules(RuleSet::American);
+    let mv = board.legal_moves()[0].clone();
+    assert_eq!(serde_json::to_string(&mv).unwrap(), format!("{{\"from\":\"{}\",\"path\":[\"{}\"],\"captures\":[]}}", mv.from, mv.to()));
+    assert!(round_trip(&mv) == mv);
+}
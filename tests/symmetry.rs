@@ -0,0 +1,69 @@
+use checkers::*;
+
+mod common;
+
+/// Positions from a few deterministic games under every rule set, including captures in progress.
+fn positions() -> Vec<Board> {
+    let mut res = vec![];
+    for rules in RuleSet::ALL {
+        for (board, mv) in common::play(rules, 150, 1) {
+            if mv.path.len() > 1 {
+                let mut partial = board.clone();
+                partial.make_move_to(mv.from, mv.path[0]);
+                res.push(partial);
+            }
+            res.push(board);
+        }
+    }
+    res
+}
+
+fn flip_move(board: &Board, mv: &Move) -> String {
+    let last = board.size()-1;
+    let flip = |cp: CellPos| cell(last-cp.row, last-cp.col);
+    Move {from: flip(mv.from), path: mv.path.iter().copied().map(flip).collect(), captures: mv.captures.iter().copied().map(flip).collect()}.to_string()
+}
+
+#[test]
+fn flipping_twice_is_identity() {
+    for board in positions() {
+        let twice = board.flip_colors().flip_colors();
+        assert_eq!(twice.to_string(), board.to_string());
+        assert_eq!(twice.hash, board.hash);
+        assert!(twice.turn == board.turn && twice.must_jump == board.must_jump);
+    }
+}
+
+#[test]
+fn flipped_hash_matches_the_position() {
+    for board in positions().into_iter().filter(|board| board.must_jump.is_empty()) {
+        let flipped = board.flip_colors();
+        assert_eq!(flipped.hash, Board::from_fen(&flipped.fen(), board.rules()).unwrap().hash);
+        assert_ne!(flipped.hash, board.hash);
+    }
+}
+
+/// `heuristic` scores for the side to move, so keeping the mover while swapping the pieces negates it.
+#[test]
+fn heuristic_is_colour_symmetric() {
+    for board in positions() {
+        let swapped = board.swap_colors();
+        assert!(swapped.turn == board.turn);
+        assert_eq!(heuristic(&board), -heuristic(&swapped), "\n{}", board);
+        assert_eq!(heuristic(&board), heuristic(&board.flip_colors()), "\n{}", board);
+    }
+}
+
+#[test]
+fn move_generation_is_colour_symmetric() {
+    for board in positions() {
+        let flipped = board.flip_colors();
+        let mut moves = board.legal_moves().iter().map(|mv| flip_move(&board, mv)).collect::<Vec<_>>();
+        let mut flipped_moves = flipped.legal_moves().iter().map(|mv| mv.to_string()).collect::<Vec<_>>();
+        moves.sort();
+        flipped_moves.sort();
+        assert_eq!(moves, flipped_moves, "\n{}", board);
+    }
+    let start = Board::with_rules(RuleSet::American);
+    assert_eq!(perft::perft(&mut start.flip_colors(), 5), perft::perft(&mut start.clone(), 5));
+}
@@ -0,0 +1,181 @@
+use std::sync::OnceLock;
+
+use checkers::*;
+
+fn american() -> &'static Tablebase {
+    static TABLEBASE: OnceLock<Tablebase> = OnceLock::new();
+    TABLEBASE.get_or_init(|| Tablebase::generate(RuleSet::American, 3, |_, _| {}).unwrap())
+}
+
+fn value_after(tablebase: &Tablebase, board: &Board, mv: &Move) -> TablebaseValue {
+    let mut child = board.clone();
+    child.apply_unchecked(mv);
+    if child.legal_moves().is_empty() {
+        TablebaseValue::Loss(Some(0))
+    }
+    else {
+        tablebase.probe(&child).unwrap()
+    }
+}
+
+/// The value of `board` worked out from the tablebase values of its replies.
+fn value_from_replies(tablebase: &Tablebase, board: &Board) -> TablebaseValue {
+    let mut fastest_win = None;
+    let mut longest_loss = Some(0);
+    for mv in board.legal_moves() {
+        match value_after(tablebase, board, &mv) {
+            TablebaseValue::Loss(Some(d)) => fastest_win = Some(fastest_win.map_or(d+1, |w: u32| w.min(d+1))),
+            TablebaseValue::Win(Some(d)) => longest_loss = longest_loss.map(|l: u32| l.max(d+1)),
+            TablebaseValue::Draw => longest_loss = None,
+            value => panic!("{:?} has no distance", value)
+        }
+    }
+    match (fastest_win, longest_loss) {
+        (Some(d), _) => TablebaseValue::Win(Some(d)),
+        (None, Some(d)) => TablebaseValue::Loss(Some(d)),
+        (None, None) => TablebaseValue::Draw
+    }
+}
+
+/// Valid positions with a piece of each side on squares `a` and `b`, and some with a third piece on `c`, either side to move.
+fn positions(rules: RuleSet) -> Vec<Board> {
+    let mut res = vec![];
+    for a in 1..=32 {
+        for b in (1..=32).filter(|&b| b != a) {
+            let mut sides = vec![(format!("K{}", a), format!("K{}", b)), (a.to_string(), format!("K{}", b)), (a.to_string(), b.to_string())];
+            if a%3 == 0 && b%3 == 1 {
+                for c in (1..=32).step_by(2).filter(|&c| c != a && c != b) {
+                    sides.push((format!("K{},{}", a, c), format!("K{}", b)));
+                    sides.push((a.to_string(), format!("{},K{}", b, c)));
+                }
+            }
+            for (white, black) in sides {
+                for turn in ["W", "B"] {
+                    if let Ok(board) = Board::from_fen(&format!("{}:W{}:B{}", turn, white, black), rules) {
+                        if board.validate().is_ok() {
+                            res.push(board);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    res
+}
+
+#[test]
+fn values_agree_with_the_replies() {
+    let russian = Tablebase::generate(RuleSet::Russian, 3, |_, _| {}).unwrap();
+    for (rules, tablebase) in [(RuleSet::American, american()), (RuleSet::Russian, &russian)] {
+        for board in positions(rules) {
+            assert_eq!(tablebase.probe(&board), Some(value_from_replies(tablebase, &board)), "{} under {}", board.fen(), rules);
+        }
+        for fen in ["W:WK14,K19:BK1", "B:WK14,K19:BK1", "W:W22,K10:BK5", "B:W9:BK28,K32", "W:W5,6:B28"] {
+            let board = Board::from_fen(fen, rules).unwrap();
+            assert_eq!(tablebase.probe(&board), Some(value_from_replies(tablebase, &board)), "{} under {}", fen, rules);
+        }
+    }
+}
+
+#[test]
+fn four_piece_values_agree_with_the_replies() {
+    let tablebase = Tablebase::generate(RuleSet::American, 4, |_, _| {}).unwrap();
+    let mut checked = 0;
+    for (a, b, c, d) in (1..=32).flat_map(|a| (1..=32).step_by(3).map(move |b| (a, b, (a*7)%32 + 1, (b*5 + a)%32 + 1))) {
+        if [a, b, c].contains(&d) || a == b || a == c || b == c {
+            continue;
+        }
+        let sides = [
+            (format!("K{},K{}", a, c), format!("K{},K{}", b, d)),
+            (format!("{},K{}", a, c), format!("{},K{}", b, d)),
+            (format!("{},{}", a, c), format!("{},{}", b, d)),
+            (format!("K{},K{},{}", a, c, d), format!("K{}", b)),
+            (format!("{},{},{}", a, c, d), b.to_string())
+        ];
+        for (white, black) in sides {
+            for turn in ["W", "B"] {
+                let Ok(board) = Board::from_fen(&format!("{}:W{}:B{}", turn, white, black), RuleSet::American) else {
+                    continue;
+                };
+                if board.validate().is_ok() {
+                    assert_eq!(tablebase.probe(&board), Some(value_from_replies(&tablebase, &board)), "{}", board.fen());
+                    checked += 1;
+                }
+            }
+        }
+    }
+    assert!(checked > 1000);
+    assert!(matches!(tablebase.probe(&Board::from_fen("W:WK1,K3,K5:BK30", RuleSet::American).unwrap()), Some(TablebaseValue::Win(_))));
+}
+
+#[test]
+fn two_kings_beat_one() {
+    let tablebase = american();
+    let mut board = Board::from_fen("W:WK14,K19:BK1", RuleSet::American).unwrap();
+    let Some(TablebaseValue::Win(Some(distance))) = tablebase.probe(&board) else {
+        panic!("two kings should win");
+    };
+    for ply in 0..distance {
+        let (mv, value) = tablebase.best_move(&board).unwrap();
+        let expected = if ply%2 == 0 { TablebaseValue::Win(Some(distance-ply)) } else { TablebaseValue::Loss(Some(distance-ply)) };
+        assert_eq!(value, expected);
+        board.apply_unchecked(&mv);
+    }
+    assert!(board.legal_moves().is_empty());
+}
+
+#[test]
+fn only_covered_positions_are_probed() {
+    let tablebase = Tablebase::generate(RuleSet::American, 2, |_, _| {}).unwrap();
+    assert_eq!(tablebase.pieces(), 2);
+    assert_eq!(tablebase.probe(&Board::from_fen("W:WK14,K19:BK1", RuleSet::American).unwrap()), None);
+    assert_eq!(tablebase.probe(&Board::from_fen("W:WK14:BK1", RuleSet::Standard).unwrap()), None);
+    assert!(tablebase.probe(&Board::from_fen("W:WK14:BK1", RuleSet::American).unwrap()).is_some());
+
+    assert_eq!(Tablebase::generate(RuleSet::International, 2, |_, _| {}).err(), Some(GenerateError::UnsupportedRules(RuleSet::International)));
+    assert_eq!(Tablebase::generate(RuleSet::American, tablebase::MAX_PIECES+1, |_, _| {}).err(), Some(GenerateError::TooManyPieces(tablebase::MAX_PIECES+1)));
+}
+
+#[test]
+fn tablebase_round_trips() {
+    let tablebase = american();
+    let mut bytes = vec![];
+    tablebase.write_to(&mut bytes).unwrap();
+    let read = Tablebase::read_from(&mut bytes.as_slice()).unwrap();
+    assert!(read.rules() == RuleSet::American);
+    assert_eq!(read.pieces(), 3);
+    for board in positions(RuleSet::American) {
+        assert_eq!(read.probe(&board), tablebase.probe(&board));
+    }
+
+    assert!(Tablebase::read_from(&mut &bytes[..bytes.len()-1]).is_err());
+    assert!(Tablebase::read_from(&mut &b"nonsense"[..]).is_err());
+}
+
+#[test]
+fn tablebase_without_distances_keeps_the_results() {
+    let tablebase = american();
+    let mut bytes = vec![];
+    tablebase.write_to(&mut bytes).unwrap();
+    let mut results = Tablebase::read_from(&mut bytes.as_slice()).unwrap();
+    results.drop_distances();
+    let mut small = vec![];
+    results.write_to(&mut small).unwrap();
+    assert!(small.len() < bytes.len()/4);
+    let read = Tablebase::read_from(&mut small.as_slice()).unwrap();
+    assert!(tablebase.has_distances() && !read.has_distances());
+
+    for board in positions(RuleSet::American) {
+        let value = read.probe(&board);
+        let expected = match tablebase.probe(&board) {
+            Some(TablebaseValue::Win(_)) => Some(TablebaseValue::Win(None)),
+            Some(TablebaseValue::Loss(_)) => Some(TablebaseValue::Loss(None)),
+            other => other
+        };
+        assert_eq!(value, expected, "{}", board.fen());
+        if value == Some(TablebaseValue::Win(None)) {
+            let (mv, _) = read.best_move(&board).unwrap();
+            assert!(matches!(value_after(tablebase, &board, &mv), TablebaseValue::Loss(_)), "{}", board.fen());
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use checkers::*;
+
+fn entry(score: i64, depth: u8, bound: Bound, best_move: Option<u16>) -> TableEntry {
+    TableEntry { score, depth, bound, best_move }
+}
+
+#[test]
+fn stored_entries_are_found() {
+    let table = TranspositionTable::new(1000);
+    assert_eq!(table.len(), 1024);
+    assert_eq!(table.probe(5), None);
+
+    for (hash, stored) in [
+        (5, entry(-1_000_300, 3, Bound::Upper, None)),
+        (6, entry(42, 255, Bound::Exact, Some(7))),
+        (7, entry(i64::MAX, 0, Bound::Lower, Some(0)))
+    ] {
+        table.store(hash, stored);
+        let found = table.probe(hash).unwrap();
+        assert_eq!(found.score, stored.score.min(i32::MAX as i64));
+        assert_eq!((found.depth, found.bound, found.best_move), (stored.depth, stored.bound, stored.best_move));
+    }
+}
+
+#[test]
+fn another_position_in_the_slot_is_a_miss() {
+    let table = TranspositionTable::new(16);
+    table.store(3, entry(1, 1, Bound::Exact, None));
+    assert_eq!(table.probe(3 + 16), None);
+    assert!(table.probe(3).is_some());
+}
+
+#[test]
+fn deeper_entries_are_kept_until_the_next_search() {
+    let mut table = TranspositionTable::new(16);
+    table.store(1, entry(10, 5, Bound::Exact, Some(2)));
+    table.store(17, entry(20, 2, Bound::Exact, None));
+    assert_eq!(table.probe(1).map(|e| e.score), Some(10));
+    assert_eq!(table.probe(17), None);
+
+    // The same position is always replaced, but keeps its best move if the new search found none.
+    table.store(1, entry(11, 1, Bound::Upper, None));
+    assert_eq!(table.probe(1), Some(entry(11, 1, Bound::Upper, Some(2))));
+
+    table.store(1, entry(10, 5, Bound::Exact, Some(2)));
+    table.new_search();
+    table.store(17, entry(20, 2, Bound::Exact, None));
+    assert_eq!(table.probe(17).map(|e| e.score), Some(20));
+    assert_eq!(table.probe(1), None);
+}
+
+#[test]
+fn statistics_count_since_the_last_search() {
+    let mut table = TranspositionTable::new(16);
+    table.store(1, entry(0, 1, Bound::Exact, None));
+    table.store(17, entry(0, 1, Bound::Exact, None));
+    table.probe(17);
+    table.probe(1);
+    assert_eq!(table.stats(), TableStats { probes: 2, hits: 1, stores: 2, overwrites: 1 });
+
+    table.new_search();
+    assert_eq!(table.stats(), TableStats::default());
+    table.clear();
+    assert_eq!(table.probe(17), None);
+}
+
+#[test]
+fn threads_share_the_table() {
+    let table = TranspositionTable::with_size_mb(1);
+    std::thread::scope(|scope| {
+        for t in 0..4u64 {
+            let table = &table;
+            scope.spawn(move || {
+                for hash in (t..4000).step_by(4) {
+                    table.store(hash.wrapping_mul(0x9E37_79B9_7F4A_7C15), entry(hash as i64, 1, Bound::Exact, None));
+                }
+            });
+        }
+    });
+    for hash in 0..4000u64 {
+        if let Some(found) = table.probe(hash.wrapping_mul(0x9E37_79B9_7F4A_7C15)) {
+            assert_eq!(found.score, hash as i64);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use checkers::*;
+
+mod common;
+
+#[test]
+fn reachable_positions_are_valid() {
+    for rules in RuleSet::ALL {
+        for seed in 0..20 {
+            for (board, mv) in common::play(rules, 200, seed) {
+                let mut partial = board;
+                let mut from = mv.from;
+                for &to in &mv.path {
+                    assert!(partial.validate().is_ok(), "{}\n{}", rules, partial);
+                    partial.make_move_to(from, to);
+                    from = to;
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn too_many_pieces() {
+    let board = Board::from_fen("B:W1-13:B21-32", RuleSet::American).unwrap();
+    let errors = board.validate().unwrap_err();
+    assert!(errors.contains(&PositionError::TooManyPieces { color: Color::White, count: 13 }));
+    assert!(!errors.iter().any(|err| matches!(err, PositionError::TooManyPieces { color: Color::Black, .. })));
+}
+
+#[test]
+fn men_are_not_crowned_on_parse() {
+    let grid = "   A B C D E F G H \n7: . w . . . . . . \n6: . . . . . . . . \n5: . . . . . . . . \n4: . . . . . . . . \n3: . . . . . . . . \n2: . . . . . . . . \n1: . . . . . . . . \n0: b . . . . . . . ";
+    let board: Board = grid.parse().unwrap();
+    assert!(!board[cell(7, 1)].piece.unwrap().king);
+    assert_eq!(board.validate(), Err(vec![PositionError::UncrownedOnPromotionRow(cell(7, 1)), PositionError::UncrownedOnPromotionRow(cell(0, 0))]));
+    assert_eq!(Board::from_fen("W:W2:B30", RuleSet::American).unwrap().validate().unwrap_err().len(), 2);
+}
+
+#[test]
+fn inconsistent_must_jump() {
+    let mut board = Board::from_fen("B:W14,25:B9,10,23", RuleSet::American).unwrap();
+    assert!(board.validate().is_ok());
+    let forced = board.must_jump.clone();
+    assert_eq!(forced.len(), 2);
+
+    board.must_jump = vec![];
+    assert_eq!(board.validate(), Err(vec![PositionError::ForcedJumpsMismatch { expected: forced.clone() }]));
+
+    let empty = board.numbered_square(18).unwrap();
+    let white = board.numbered_square(25).unwrap();
+    let quiet = board.numbered_square(23).unwrap();
+    board.must_jump = vec![empty, white, quiet];
+    let errors = board.validate().unwrap_err();
+    assert!(errors.contains(&PositionError::MustJumpWithoutPiece(empty)));
+    assert!(errors.contains(&PositionError::MustJumpWithoutPiece(white)));
+    assert!(errors.contains(&PositionError::MustJumpWithoutCapture(quiet)));
+    assert!(errors.contains(&PositionError::ForcedJumpsMismatch { expected: forced }));
+
+    // A single piece is taken to be in the middle of a capture.
+    board.must_jump = vec![board.numbered_square(9).unwrap()];
+    assert!(board.validate().is_ok());
+    assert_eq!(describe_errors(&[PositionError::MustJumpWithoutCapture(quiet)]), "must jump from E2 without a capture");
+}